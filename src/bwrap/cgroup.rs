@@ -0,0 +1,192 @@
+// Copyright (C) 2025 Pierre Le Gall
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Cgroup v2 resource-limit enforcement for the sandboxed process: creates
+//! a delegated subtree under the caller's own cgroup, writes the
+//! configured `memory.max`/`cpu.max`/`pids.max` into it, and adds a PID to
+//! its `cgroup.procs`.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::ResourceLimits;
+
+/// Base of the cgroup v2 unified hierarchy.
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// Controllers `limits` needs enabled in the parent's `cgroup.controllers`
+/// before a child subtree can use them.
+fn required_controllers(limits: &ResourceLimits) -> Vec<&'static str> {
+    let mut controllers = Vec::new();
+    if limits.memory_max.is_some() {
+        controllers.push("memory");
+    }
+    if limits.cpu_quota.is_some() || limits.cpu_period.is_some() {
+        controllers.push("cpu");
+    }
+    if limits.pids_max.is_some() {
+        controllers.push("pids");
+    }
+    controllers
+}
+
+/// Find this process's own cgroup v2 path under `/sys/fs/cgroup`, by
+/// reading the unified-hierarchy line in `/proc/self/cgroup` (the one
+/// with an empty controller list: `0::<path>`). A host running cgroup v1
+/// (or a hybrid hierarchy with no v2 unified mount) has no such line.
+fn own_cgroup_path() -> Result<PathBuf> {
+    let contents = fs::read_to_string("/proc/self/cgroup").context("Failed to read /proc/self/cgroup")?;
+
+    let line = contents.lines().find(|line| line.starts_with("0::")).context(
+        "No cgroup v2 unified-hierarchy entry in /proc/self/cgroup; \
+         this host appears to be cgroup v1-only, so `resources` limits can't be enforced",
+    )?;
+
+    let relative = line.trim_start_matches("0::").trim_start_matches('/');
+    Ok(Path::new(CGROUP_ROOT).join(relative))
+}
+
+/// Check that every controller `limits` needs is listed in `dir`'s
+/// `cgroup.controllers` (the controllers available to this cgroup's
+/// children), failing with a message pointing at `cgroup.subtree_control`
+/// otherwise.
+fn check_controllers_enabled(dir: &Path, limits: &ResourceLimits) -> Result<()> {
+    let controllers_file = dir.join("cgroup.controllers");
+    let enabled = fs::read_to_string(&controllers_file).with_context(|| {
+        format!(
+            "Failed to read {:?}; is cgroup v2 mounted at {}?",
+            controllers_file, CGROUP_ROOT
+        )
+    })?;
+    let enabled: HashSet<&str> = enabled.split_whitespace().collect();
+
+    for controller in required_controllers(limits) {
+        if !enabled.contains(controller) {
+            bail!(
+                "cgroup v2 controller '{}' isn't enabled in {:?}; add it to the parent's \
+                 cgroup.subtree_control to delegate it to child cgroups",
+                controller,
+                controllers_file
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Create a delegated subtree named `name` under this process's own
+/// cgroup and write `limits`'s controller files into it, returning the
+/// subtree's path so the caller can add a process to its `cgroup.procs`.
+pub fn create_delegated_subtree(name: &str, limits: &ResourceLimits) -> Result<PathBuf> {
+    let parent = own_cgroup_path()?;
+    check_controllers_enabled(&parent, limits)?;
+
+    let subtree = parent.join(name);
+    fs::create_dir_all(&subtree).with_context(|| format!("Failed to create delegated cgroup {:?}", subtree))?;
+
+    if let Some(memory_max) = limits.memory_max {
+        write_controller_file(&subtree.join("memory.max"), &memory_max.to_string())?;
+    }
+    if limits.cpu_quota.is_some() || limits.cpu_period.is_some() {
+        let quota = limits.cpu_quota.map_or("max".to_string(), |q| q.to_string());
+        let period = limits.cpu_period.unwrap_or(100_000);
+        write_controller_file(&subtree.join("cpu.max"), &format!("{} {}", quota, period))?;
+    }
+    if let Some(pids_max) = limits.pids_max {
+        write_controller_file(&subtree.join("pids.max"), &pids_max.to_string())?;
+    }
+
+    Ok(subtree)
+}
+
+fn write_controller_file(path: &Path, value: &str) -> Result<()> {
+    fs::write(path, value).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// Add `pid` to `subtree`'s `cgroup.procs`, placing it under the limits
+/// already written there.
+pub fn add_process(subtree: &Path, pid: u32) -> Result<()> {
+    write_controller_file(&subtree.join("cgroup.procs"), &pid.to_string())
+}
+
+/// Render `limits` the way `cgroupfs` would see them, for `show()`'s
+/// dry-run comment (e.g. `memory.max=536870912 cpu.max="50000 100000"`).
+/// Only the controllers actually set in `limits` are listed.
+pub fn describe(limits: &ResourceLimits) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(memory_max) = limits.memory_max {
+        parts.push(format!("memory.max={}", memory_max));
+    }
+    if limits.cpu_quota.is_some() || limits.cpu_period.is_some() {
+        let quota = limits.cpu_quota.map_or("max".to_string(), |q| q.to_string());
+        let period = limits.cpu_period.unwrap_or(100_000);
+        parts.push(format!("cpu.max=\"{} {}\"", quota, period));
+    }
+    if let Some(pids_max) = limits.pids_max {
+        parts.push(format!("pids.max={}", pids_max));
+    }
+
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_controllers_empty_for_default_limits() {
+        assert!(required_controllers(&ResourceLimits::default()).is_empty());
+    }
+
+    #[test]
+    fn test_required_controllers_cpu_from_either_quota_or_period() {
+        let quota_only = ResourceLimits {
+            cpu_quota: Some(50_000),
+            ..Default::default()
+        };
+        assert_eq!(required_controllers(&quota_only), vec!["cpu"]);
+
+        let period_only = ResourceLimits {
+            cpu_period: Some(100_000),
+            ..Default::default()
+        };
+        assert_eq!(required_controllers(&period_only), vec!["cpu"]);
+    }
+
+    #[test]
+    fn test_required_controllers_all_three() {
+        let limits = ResourceLimits {
+            memory_max: Some(1),
+            cpu_quota: Some(1),
+            cpu_period: Some(1),
+            pids_max: Some(1),
+        };
+        assert_eq!(required_controllers(&limits), vec!["memory", "cpu", "pids"]);
+    }
+
+    #[test]
+    fn test_describe_only_lists_set_controllers() {
+        let limits = ResourceLimits {
+            memory_max: Some(536_870_912),
+            ..Default::default()
+        };
+        assert_eq!(describe(&limits), "memory.max=536870912");
+    }
+
+    #[test]
+    fn test_describe_cpu_defaults_missing_quota_to_max() {
+        let limits = ResourceLimits {
+            cpu_period: Some(100_000),
+            ..Default::default()
+        };
+        assert_eq!(describe(&limits), "cpu.max=\"max 100000\"");
+    }
+
+    #[test]
+    fn test_describe_empty_limits() {
+        assert_eq!(describe(&ResourceLimits::default()), "");
+    }
+}
@@ -1,17 +1,116 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use libseccomp::{ScmpAction, ScmpFilterContext, ScmpSyscall};
+use std::fs::File;
+use std::io::Write;
+use std::os::fd::{AsRawFd, FromRawFd, RawFd};
 use std::process::Command;
+use thiserror::Error;
 
-use crate::config::Entry;
+use crate::config::{Entry, SeccompAction, SeccompProfile};
+
+pub mod cgroup;
 
 const NAMESPACES: [&str; 6] = ["user", "pid", "network", "ipc", "uts", "cgroup"];
 
+/// The Linux `CAP_*` capabilities bwrap's `--cap-add`/`--cap-drop` accept
+/// (the same enumeration OCI runtimes use), so a typo in `Entry::cap`/
+/// `Entry::cap_drop` is rejected here instead of producing a bwrap
+/// invocation it refuses to run.
+const KNOWN_CAPABILITIES: &[&str] = &[
+    "CAP_CHOWN",
+    "CAP_DAC_OVERRIDE",
+    "CAP_DAC_READ_SEARCH",
+    "CAP_FOWNER",
+    "CAP_FSETID",
+    "CAP_KILL",
+    "CAP_SETGID",
+    "CAP_SETUID",
+    "CAP_SETPCAP",
+    "CAP_LINUX_IMMUTABLE",
+    "CAP_NET_BIND_SERVICE",
+    "CAP_NET_BROADCAST",
+    "CAP_NET_ADMIN",
+    "CAP_NET_RAW",
+    "CAP_IPC_LOCK",
+    "CAP_IPC_OWNER",
+    "CAP_SYS_MODULE",
+    "CAP_SYS_RAWIO",
+    "CAP_SYS_CHROOT",
+    "CAP_SYS_PTRACE",
+    "CAP_SYS_PACCT",
+    "CAP_SYS_ADMIN",
+    "CAP_SYS_BOOT",
+    "CAP_SYS_NICE",
+    "CAP_SYS_RESOURCE",
+    "CAP_SYS_TIME",
+    "CAP_SYS_TTY_CONFIG",
+    "CAP_MKNOD",
+    "CAP_LEASE",
+    "CAP_AUDIT_WRITE",
+    "CAP_AUDIT_CONTROL",
+    "CAP_SETFCAP",
+    "CAP_MAC_OVERRIDE",
+    "CAP_MAC_ADMIN",
+    "CAP_SYSLOG",
+    "CAP_WAKE_ALARM",
+    "CAP_BLOCK_SUSPEND",
+    "CAP_AUDIT_READ",
+    "CAP_PERFMON",
+    "CAP_BPF",
+    "CAP_CHECKPOINT_RESTORE",
+];
+
+/// Token accepted in `cap_drop` (but not `cap`) meaning "every capability",
+/// matching the bounding-set convention other container runtimes use for
+/// dropping everything and re-adding a minimal allowlist.
+const ALL_CAPABILITIES: &str = "ALL";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CapabilityError {
+    #[error("unknown capability '{name}' in `{field}` (expected a CAP_* name{all_hint})")]
+    Unknown {
+        field: &'static str,
+        name: String,
+        all_hint: &'static str,
+    },
+}
+
+/// Check `name` against `KNOWN_CAPABILITIES`, additionally accepting
+/// `ALL_CAPABILITIES` when `field` is `cap_drop`.
+fn validate_cap(field: &'static str, name: &str) -> Result<(), CapabilityError> {
+    if field == "cap_drop" && name == ALL_CAPABILITIES {
+        return Ok(());
+    }
+    if KNOWN_CAPABILITIES.contains(&name) {
+        return Ok(());
+    }
+    Err(CapabilityError::Unknown {
+        field,
+        name: name.to_string(),
+        all_hint: if field == "cap_drop" { ", or 'ALL'" } else { "" },
+    })
+}
+
 pub struct WrappedCommandBuilder {
     config: Entry,
 }
 
 impl WrappedCommandBuilder {
-    pub fn new(config: Entry) -> Self {
-        Self { config }
+    pub fn new(config: Entry) -> Result<Self, CapabilityError> {
+        Self::validate(&config)?;
+        Ok(Self { config })
+    }
+
+    /// Reject unknown `CAP_*` names in `cap`/`cap_drop` up front, so a typo
+    /// fails loudly here rather than as a bwrap error deep in `exec`.
+    fn validate(config: &Entry) -> Result<(), CapabilityError> {
+        for cap in &config.cap {
+            validate_cap("cap", cap)?;
+        }
+        for cap in &config.cap_drop {
+            validate_cap("cap_drop", cap)?;
+        }
+        Ok(())
     }
 
     /// Build the bwrap command arguments
@@ -113,6 +212,13 @@ impl WrappedCommandBuilder {
             args.push(expanded.to_string());
         }
 
+        // Handle cap-drop (before cap-add, so "drop ALL then re-add X"
+        // reads in the order it takes effect)
+        for cap in &self.config.cap_drop {
+            args.push("--cap-drop".to_string());
+            args.push(cap.clone());
+        }
+
         // Handle cap
         for cap in &self.config.cap {
             args.push("--cap-add".to_string());
@@ -137,28 +243,169 @@ impl WrappedCommandBuilder {
 
     /// Execute a command with bwrap
     pub fn exec(&self, command: &str, command_args: &[String]) -> Result<i32> {
-        let bwrap_args = self.build_args();
+        let mut bwrap_args = self.build_args();
+        // Kept alive until the child has forked and read it: dropping this
+        // early would close the pipe's read end before bwrap gets to it.
+        let _seccomp_fd = self.push_seccomp_arg(&mut bwrap_args)?;
 
         let mut cmd = Command::new("bwrap");
         cmd.args(&bwrap_args);
         cmd.arg(command);
         cmd.args(command_args);
 
-        let status = cmd.status()?;
+        // The subtree (and its controller files) must exist before bwrap
+        // starts, but adding bwrap's own pid to `cgroup.procs` happens
+        // from the parent, right after `spawn`, rather than from a
+        // `pre_exec` hook in the forked child: `cgroup::add_process`
+        // shells out to a plain `fs::write`, which isn't async-signal-safe
+        // and would risk a malloc deadlock if run between fork(2) and
+        // execvp(2). bwrap hasn't forked into the sandboxed command yet at
+        // this point, and cgroup membership is inherited by children, so
+        // the command still starts out under the limits.
+        let subtree = match &self.config.resources {
+            Some(limits) => Some(cgroup::create_delegated_subtree(&format!("sheld-{}", std::process::id()), limits)?),
+            None => None,
+        };
+
+        let mut child = cmd.spawn()?;
+
+        if let Some(subtree) = &subtree {
+            cgroup::add_process(subtree, child.id())?;
+        }
+
+        let status = child.wait()?;
         Ok(status.code().unwrap_or(1))
     }
 
+    /// If `config.seccomp` is set, compile it to a BPF program, write it to
+    /// a pipe, and push `--seccomp <fd>` onto `args`. Returns the read end
+    /// of the pipe so the caller can keep it open until after bwrap has
+    /// forked; the fd is plain (no `O_CLOEXEC`), so it survives the
+    /// fork+exec into bwrap without any extra plumbing.
+    fn push_seccomp_arg(&self, args: &mut Vec<String>) -> Result<Option<File>> {
+        let Some(profile) = &self.config.seccomp else {
+            return Ok(None);
+        };
+
+        let program = compile_seccomp_program(profile)?;
+        let read_end = seccomp_pipe(&program)?;
+        args.push("--seccomp".to_string());
+        args.push(read_end.as_raw_fd().to_string());
+        Ok(Some(read_end))
+    }
+
     /// Show the bwrap command that would be executed (dry-run)
     pub fn show(&self, command: &str, command_args: &[String]) -> String {
-        let bwrap_args = self.build_args();
+        let mut bwrap_args = self.build_args();
+        if self.config.seccomp.is_some() {
+            bwrap_args.push("--seccomp".to_string());
+            bwrap_args.push("<fd>".to_string());
+        }
 
         let mut parts = vec!["bwrap".to_string()];
         parts.extend(bwrap_args);
         parts.push(command.to_string());
         parts.extend(command_args.iter().cloned());
 
-        parts.join(" ")
+        let mut rendered = parts.iter().map(|part| shell_quote(part)).collect::<Vec<_>>().join(" ");
+        if let Some(profile) = &self.config.seccomp {
+            rendered.push_str(&format!(
+                "  # <fd> carries a compiled seccomp profile ({} rule(s), default {})",
+                profile.rules.len(),
+                profile.default_action
+            ));
+        }
+        if let Some(limits) = &self.config.resources {
+            rendered.push_str(&format!("  # cgroup limits: {}", cgroup::describe(limits)));
+        }
+        rendered
+    }
+}
+
+/// Bytes that can appear bare in a POSIX shell word without being
+/// misinterpreted (no quoting, expansion, or globbing characters).
+fn is_shell_safe_byte(byte: u8) -> bool {
+    matches!(byte, b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'/' | b':' | b'=' | b'@' | b'+' | b',')
+}
+
+/// Quote `token` so `show()`'s printed `bwrap ...` line is an exact,
+/// copy-pasteable reproduction of the `Command` `exec` actually spawns:
+/// left bare when every byte is shell-safe, otherwise single-quote-wrapped
+/// with embedded `'` escaped as the standard POSIX `'\''` (close the
+/// quote, an escaped literal quote, reopen the quote).
+fn shell_quote(token: &str) -> String {
+    if !token.is_empty() && token.bytes().all(is_shell_safe_byte) {
+        return token.to_string();
+    }
+
+    let mut quoted = String::with_capacity(token.len() + 2);
+    quoted.push('\'');
+    for ch in token.chars() {
+        if ch == '\'' {
+            quoted.push_str("'\\''");
+        } else {
+            quoted.push(ch);
+        }
     }
+    quoted.push('\'');
+    quoted
+}
+
+/// Compile an OCI-style `SeccompProfile` into a classic BPF program, the
+/// form bwrap expects on the fd passed via `--seccomp`.
+fn compile_seccomp_program(profile: &SeccompProfile) -> Result<Vec<u8>> {
+    let mut filter = ScmpFilterContext::new_filter(to_scmp_action(profile.default_action))
+        .context("Failed to create seccomp filter")?;
+
+    for rule in &profile.rules {
+        let action = to_scmp_action(rule.action);
+        for name in &rule.names {
+            let syscall = ScmpSyscall::from_name(name)
+                .with_context(|| format!("Unknown syscall '{}' in seccomp profile", name))?;
+            filter
+                .add_rule(action, syscall)
+                .with_context(|| format!("Failed to add seccomp rule for '{}'", name))?;
+        }
+    }
+
+    let mut program = Vec::new();
+    filter
+        .export_bpf(&mut program)
+        .context("Failed to compile seccomp profile to BPF")?;
+    Ok(program)
+}
+
+fn to_scmp_action(action: SeccompAction) -> ScmpAction {
+    match action {
+        SeccompAction::Allow => ScmpAction::Allow,
+        SeccompAction::Errno => ScmpAction::Errno(libc::EPERM),
+        SeccompAction::Kill => ScmpAction::KillProcess,
+        SeccompAction::Trap => ScmpAction::Trap,
+        SeccompAction::Log => ScmpAction::Log,
+    }
+}
+
+/// Write `program` to a pipe and return the read end, left open for the
+/// caller to hold onto. The write end is closed as soon as the program is
+/// flushed, so bwrap sees EOF once it has read the whole filter.
+fn seccomp_pipe(program: &[u8]) -> Result<File> {
+    let mut fds: [RawFd; 2] = [0, 0];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(std::io::Error::last_os_error()).context("Failed to create seccomp pipe");
+    }
+    let (read_fd, write_fd) = (fds[0], fds[1]);
+
+    // SAFETY: write_fd was just returned by pipe(2) above and isn't owned
+    // anywhere else yet.
+    let mut write_end = unsafe { File::from_raw_fd(write_fd) };
+    write_end
+        .write_all(program)
+        .context("Failed to write seccomp BPF program to pipe")?;
+    // Dropping write_end here closes write_fd, signalling EOF to bwrap.
+
+    // SAFETY: read_fd was just returned by pipe(2) above and isn't owned
+    // anywhere else yet.
+    Ok(unsafe { File::from_raw_fd(read_fd) })
 }
 
 #[cfg(test)]
@@ -186,8 +433,14 @@ mod tests {
             die_with_parent: false,
             new_session: false,
             cap: vec![],
+            cap_drop: vec![],
             env: HashMap::new(),
             unset_env: vec![],
+            seccomp: None,
+            resources: None,
+            profiles: HashMap::new(),
+            alias: None,
+            args: vec![],
         }
     }
 
@@ -196,7 +449,7 @@ mod tests {
         let config = create_test_config();
         // Empty config = all namespaces unshared by default
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         assert!(args.contains(&"--unshare-net".to_string()));
@@ -213,7 +466,7 @@ mod tests {
         // share now controls namespace sharing, not filesystem paths
         config.share = vec!["network".to_string(), "user".to_string()];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         // Network and user should NOT be unshared
@@ -232,7 +485,7 @@ mod tests {
         let mut config = create_test_config();
         config.bind = vec![("/src".to_string(), "/dest".to_string())];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         let bind_idx = args.iter().position(|x| x == "--bind").unwrap();
@@ -245,7 +498,7 @@ mod tests {
         let mut config = create_test_config();
         config.ro_bind = vec![("/usr".to_string(), "/usr".to_string())];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         assert!(args.contains(&"--ro-bind".to_string()));
@@ -257,7 +510,7 @@ mod tests {
         let mut config = create_test_config();
         config.dev_bind = vec![("/dev/null".to_string(), "/dev/null".to_string())];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         assert!(args.contains(&"--dev-bind".to_string()));
@@ -269,7 +522,7 @@ mod tests {
         let mut config = create_test_config();
         config.tmpfs = vec!["/tmp".to_string(), "/var/tmp".to_string()];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         assert!(args.contains(&"--tmpfs".to_string()));
@@ -285,7 +538,7 @@ mod tests {
             .insert("NODE_ENV".to_string(), "production".to_string());
         config.env.insert("DEBUG".to_string(), "true".to_string());
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         let setenv_count = args.iter().filter(|x| *x == "--setenv").count();
@@ -299,7 +552,7 @@ mod tests {
         let mut config = create_test_config();
         config.unset_env = vec!["DEBUG".to_string(), "VERBOSE".to_string()];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         assert!(args.contains(&"--unsetenv".to_string()));
@@ -314,7 +567,7 @@ mod tests {
         config.ro_bind = vec![("/usr".to_string(), "/usr".to_string())];
         config.env.insert("TEST".to_string(), "value".to_string());
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         // Check all types are present
@@ -329,7 +582,7 @@ mod tests {
         let mut config = create_test_config();
         config.share = vec!["user".to_string()]; // Share user, unshare rest
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let cmd = builder.show("node", &["script.js".to_string()]);
 
         assert!(cmd.starts_with("bwrap"));
@@ -341,7 +594,7 @@ mod tests {
     #[test]
     fn test_show_command_with_multiple_args() {
         let config = create_test_config();
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let cmd = builder.show(
             "git",
             &[
@@ -357,10 +610,70 @@ mod tests {
         assert!(cmd.contains("message"));
     }
 
+    #[test]
+    fn test_shell_quote_leaves_safe_tokens_bare() {
+        assert_eq!(shell_quote("node"), "node");
+        assert_eq!(shell_quote("--bind"), "--bind");
+        assert_eq!(shell_quote("/usr/bin/env"), "/usr/bin/env");
+    }
+
+    #[test]
+    fn test_shell_quote_wraps_token_with_spaces() {
+        assert_eq!(shell_quote("/path with spaces/bin"), "'/path with spaces/bin'");
+    }
+
+    #[test]
+    fn test_shell_quote_wraps_token_with_dollar() {
+        // Bare would let the shell expand $HOME on paste-back.
+        assert_eq!(shell_quote("$HOME/.cache"), "'$HOME/.cache'");
+    }
+
+    #[test]
+    fn test_shell_quote_escapes_embedded_single_quote() {
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn test_shell_quote_empty_string_is_quoted() {
+        // An empty arg must still round-trip as an argument, not vanish.
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[test]
+    fn test_show_quotes_bind_path_with_spaces() {
+        let mut config = create_test_config();
+        config.bind = vec![("/src with spaces".to_string(), "/dest".to_string())];
+
+        let builder = WrappedCommandBuilder::new(config).unwrap();
+        let cmd = builder.show("node", &[]);
+
+        assert!(cmd.contains("'/src with spaces'"));
+    }
+
+    #[test]
+    fn test_show_quotes_env_value_with_dollar_sign() {
+        let mut config = create_test_config();
+        config.env.insert("GREETING".to_string(), "$USER says hi".to_string());
+
+        let builder = WrappedCommandBuilder::new(config).unwrap();
+        let cmd = builder.show("node", &[]);
+
+        assert!(cmd.contains("'$USER says hi'"));
+    }
+
+    #[test]
+    fn test_show_quotes_argument_with_single_quote() {
+        let config = create_test_config();
+        let builder = WrappedCommandBuilder::new(config).unwrap();
+        let cmd = builder.show("echo", &["it's a test".to_string()]);
+
+        assert!(cmd.contains("'it'\\''s a test'"));
+    }
+
     #[test]
     fn test_empty_config() {
         let config = create_test_config();
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         // Empty config should unshare all namespaces by default
@@ -377,7 +690,7 @@ mod tests {
         let mut config = create_test_config();
         config.bind = vec![("~/.config".to_string(), "~/.config".to_string())];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         // shellexpand should expand ~ to home directory
@@ -390,7 +703,7 @@ mod tests {
     #[test]
     fn test_unshare_all_by_default() {
         let config = create_test_config();
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         // All namespaces should be unshared by default
@@ -407,7 +720,7 @@ mod tests {
         let mut config = create_test_config();
         config.share = vec!["user".to_string(), "network".to_string()];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         // User and network should NOT be unshared (they are shared)
@@ -433,7 +746,7 @@ mod tests {
             "cgroup".to_string(),
         ];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         // No namespaces should be unshared
@@ -450,7 +763,7 @@ mod tests {
         let mut config = create_test_config();
         config.bind_try = vec![("~/.cache".to_string(), "~/.cache".to_string())];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         let bind_try_idx = args.iter().position(|x| x == "--bind-try").unwrap();
@@ -464,7 +777,7 @@ mod tests {
         let mut config = create_test_config();
         config.ro_bind_try = vec![("/usr".to_string(), "/usr".to_string())];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         assert!(args.contains(&"--ro-bind-try".to_string()));
@@ -476,7 +789,7 @@ mod tests {
         let mut config = create_test_config();
         config.dev_bind_try = vec![("/dev/kvm".to_string(), "/dev/kvm".to_string())];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         assert!(args.contains(&"--dev-bind-try".to_string()));
@@ -488,7 +801,7 @@ mod tests {
         let mut config = create_test_config();
         config.chdir = Some("/workspace".to_string());
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         let chdir_idx = args.iter().position(|x| x == "--chdir").unwrap();
@@ -500,7 +813,7 @@ mod tests {
         let mut config = create_test_config();
         config.chdir = Some("~/projects".to_string());
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         let chdir_idx = args.iter().position(|x| x == "--chdir").unwrap();
@@ -513,7 +826,7 @@ mod tests {
         let config = create_test_config();
         // chdir is None by default
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         // Should not contain --chdir
@@ -525,7 +838,7 @@ mod tests {
         let mut config = create_test_config();
         config.die_with_parent = true;
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         assert!(args.contains(&"--die-with-parent".to_string()));
@@ -536,7 +849,7 @@ mod tests {
         let config = create_test_config();
         // die_with_parent is false by default
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         // Should not contain --die-with-parent
@@ -548,7 +861,7 @@ mod tests {
         let mut config = create_test_config();
         config.new_session = true;
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         assert!(args.contains(&"--new-session".to_string()));
@@ -559,7 +872,7 @@ mod tests {
         let mut config = create_test_config();
         config.cap = vec!["CAP_SYS_ADMIN".to_string()];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         let cap_add_idx = args.iter().position(|x| x == "--cap-add").unwrap();
@@ -575,7 +888,7 @@ mod tests {
             "CAP_SYS_TIME".to_string(),
         ];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         let cap_add_count = args.iter().filter(|x| *x == "--cap-add").count();
@@ -590,13 +903,69 @@ mod tests {
         let config = create_test_config();
         // capabilities is empty by default
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         // Should not contain --cap-add
         assert!(!args.contains(&"--cap-add".to_string()));
     }
 
+    #[test]
+    fn test_cap_rejects_unknown_name() {
+        let mut config = create_test_config();
+        config.cap = vec!["CAP_SYS_ADMNI".to_string()];
+
+        let err = WrappedCommandBuilder::new(config).unwrap_err();
+        assert!(matches!(err, CapabilityError::Unknown { ref name, .. } if name == "CAP_SYS_ADMNI"));
+    }
+
+    #[test]
+    fn test_cap_drop_single() {
+        let mut config = create_test_config();
+        config.cap_drop = vec!["CAP_SYS_ADMIN".to_string()];
+
+        let builder = WrappedCommandBuilder::new(config).unwrap();
+        let args = builder.build_args();
+
+        let cap_drop_idx = args.iter().position(|x| x == "--cap-drop").unwrap();
+        assert_eq!(args[cap_drop_idx + 1], "CAP_SYS_ADMIN");
+    }
+
+    #[test]
+    fn test_cap_drop_accepts_all_token() {
+        let mut config = create_test_config();
+        config.cap_drop = vec!["ALL".to_string()];
+        config.cap = vec!["CAP_NET_BIND_SERVICE".to_string()];
+
+        let builder = WrappedCommandBuilder::new(config).unwrap();
+        let args = builder.build_args();
+
+        assert!(args.contains(&"ALL".to_string()));
+    }
+
+    #[test]
+    fn test_cap_rejects_all_token_in_cap() {
+        let mut config = create_test_config();
+        config.cap = vec!["ALL".to_string()];
+
+        let err = WrappedCommandBuilder::new(config).unwrap_err();
+        assert!(matches!(err, CapabilityError::Unknown { ref name, .. } if name == "ALL"));
+    }
+
+    #[test]
+    fn test_cap_drop_emitted_before_cap_add() {
+        let mut config = create_test_config();
+        config.cap_drop = vec!["ALL".to_string()];
+        config.cap = vec!["CAP_NET_BIND_SERVICE".to_string()];
+
+        let builder = WrappedCommandBuilder::new(config).unwrap();
+        let args = builder.build_args();
+
+        let drop_idx = args.iter().position(|x| x == "--cap-drop").unwrap();
+        let add_idx = args.iter().position(|x| x == "--cap-add").unwrap();
+        assert!(drop_idx < add_idx);
+    }
+
     #[test]
     fn test_all_new_options_combined() {
         let mut config = create_test_config();
@@ -607,7 +976,7 @@ mod tests {
         config.new_session = true;
         config.cap = vec!["CAP_SYS_ADMIN".to_string()];
 
-        let builder = WrappedCommandBuilder::new(config);
+        let builder = WrappedCommandBuilder::new(config).unwrap();
         let args = builder.build_args();
 
         // Check all new options are present
@@ -618,4 +987,68 @@ mod tests {
         assert!(args.contains(&"--chdir".to_string()));
         assert!(args.contains(&"--cap-add".to_string()));
     }
+
+    #[test]
+    fn test_show_seccomp_none() {
+        let config = create_test_config();
+        // seccomp is None by default
+
+        let builder = WrappedCommandBuilder::new(config).unwrap();
+        let cmd = builder.show("node", &[]);
+
+        assert!(!cmd.contains("--seccomp"));
+    }
+
+    #[test]
+    fn test_show_seccomp_renders_placeholder_fd_and_note() {
+        use crate::config::{SeccompAction, SeccompProfile, SeccompRule};
+
+        let mut config = create_test_config();
+        config.seccomp = Some(SeccompProfile {
+            default_action: SeccompAction::Errno,
+            rules: vec![SeccompRule {
+                names: vec!["execve".to_string(), "clone".to_string()],
+                action: SeccompAction::Allow,
+            }],
+        });
+
+        let builder = WrappedCommandBuilder::new(config).unwrap();
+        let cmd = builder.show("node", &[]);
+
+        assert!(cmd.contains("--seccomp '<fd>'"));
+        assert!(cmd.contains("1 rule(s)"));
+        assert!(cmd.contains("SCMP_ACT_ERRNO"));
+    }
+
+    #[test]
+    fn test_show_resources_none() {
+        let config = create_test_config();
+        // resources is None by default
+
+        let builder = WrappedCommandBuilder::new(config).unwrap();
+        let cmd = builder.show("node", &[]);
+
+        assert!(!cmd.contains("cgroup limits"));
+    }
+
+    #[test]
+    fn test_show_resources_renders_limits_as_comment() {
+        use crate::config::ResourceLimits;
+
+        let mut config = create_test_config();
+        config.resources = Some(ResourceLimits {
+            memory_max: Some(536_870_912),
+            cpu_quota: Some(50_000),
+            cpu_period: Some(100_000),
+            pids_max: Some(64),
+        });
+
+        let builder = WrappedCommandBuilder::new(config).unwrap();
+        let cmd = builder.show("node", &[]);
+
+        assert!(cmd.contains("# cgroup limits:"));
+        assert!(cmd.contains("memory.max=536870912"));
+        assert!(cmd.contains("cpu.max=\"50000 100000\""));
+        assert!(cmd.contains("pids.max=64"));
+    }
 }
@@ -1,30 +1,95 @@
 // Copyright (C) 2025 Pierre Le Gall
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+
+pub mod suggest;
+
+/// Value parser for `Validate { path }`: reject a missing/unreadable file
+/// at parse time instead of failing deep inside `Config::from_file`.
+fn validate_config_path(path: &str) -> Result<String, String> {
+    let p = std::path::Path::new(path);
+    if !p.exists() {
+        return Err(format!("config file not found: {}", path));
+    }
+    std::fs::File::open(p).map_err(|e| format!("cannot read config file {}: {}", path, e))?;
+    Ok(path.to_string())
+}
 
 #[derive(Parser)]
 #[command(name = "sheld")]
 #[command(about = "A profile manager for Bubblewrap (bwrap)", long_about = None)]
 pub struct Cli {
+    /// Load this config file instead of searching the .sheld.yaml hierarchy
+    #[arg(long = "config", short = 'c', global = true)]
+    pub config: Option<String>,
+
+    /// Disable colored output
+    #[arg(long = "no-color", global = true)]
+    pub no_color: bool,
+
+    /// Control whether output is colored
+    #[arg(long = "color", global = true, default_value = "auto")]
+    pub color: ColorChoice,
+
+    /// Print extra detail about what sheld is doing (color setting, config path)
+    #[arg(long, short, global = true)]
+    pub verbose: bool,
+
     #[command(subcommand)]
     pub action: Action,
 }
 
+/// When to emit ANSI color codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl Cli {
+    /// Resolve the effective color choice, honoring `--no-color` over `--color`.
+    pub fn use_color(&self) -> bool {
+        if self.no_color {
+            return false;
+        }
+        match self.color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => std::io::IsTerminal::is_terminal(&std::io::stdout()),
+        }
+    }
+}
+
 #[derive(Subcommand)]
 pub enum Action {
     /// Initialize a new .sheld.yaml file
-    Init,
+    Init {
+        /// Skip the interactive prompt and use the named profile
+        /// (e.g. minimal, network-isolated, dev-tool, browser, custom)
+        #[arg(long)]
+        profile: Option<String>,
+    },
 
     /// List active profiles and configurations
     List {
         /// To enable simple output (useful for shell inputs)
         #[arg(long)]
         simple: bool,
+
+        /// Annotate each command with the config file it came from
+        #[arg(long)]
+        show_origin: bool,
     },
 
     /// Manually wrap and execute a command
     Wrap {
+        /// Apply this named profile (from the command's `profiles:` map) on
+        /// top of its base entry
+        #[arg(long)]
+        profile: Option<String>,
+
         /// Command to execute
         command: String,
 
@@ -45,6 +110,18 @@ pub enum Action {
 
     /// Show the bwrap command that would be executed
     Show {
+        /// Annotate the resolved share/bind/enabled fields with the config
+        /// file they came from. Must come before `command`: `args` below
+        /// is a trailing catch-all, so anything after `command` (including
+        /// a `--` flag) is treated as an argument to the wrapped command.
+        #[arg(long)]
+        show_origin: bool,
+
+        /// Apply this named profile (from the command's `profiles:` map) on
+        /// top of its base entry
+        #[arg(long)]
+        profile: Option<String>,
+
         /// Command to show
         command: String,
 
@@ -56,10 +133,19 @@ pub enum Action {
     /// Validate configuration syntax
     Validate {
         /// Path to config file (defaults to searching hierarchy)
+        #[arg(value_parser = validate_config_path)]
         path: Option<String>,
         /// To enable no output (useful for shell exit code returns)
         #[arg(long)]
         silent: bool,
+        /// Annotate each command with the config file it came from
+        #[arg(long)]
+        show_origin: bool,
+        /// Fail (with a nonzero exit code) if the user and local configs
+        /// define the same command with conflicting share/bind/enabled
+        /// values, instead of just warning about the shadowing
+        #[arg(long)]
+        strict: bool,
     },
 
     /// Get shell integration code for activation
@@ -68,6 +154,22 @@ pub enum Action {
         shell: String,
     },
 
+    /// Generate a tab-completion script for the given shell
+    Completions {
+        /// Shell name (bash, zsh, fish, elvish, powershell)
+        shell: String,
+    },
+
+    /// Show the merged effective configuration, annotated with its source file
+    Explain {
+        /// Scope the output to just this command's rules
+        command: Option<String>,
+
+        /// Emit machine-readable JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Check if a command exists in configuration
     Check {
         /// Command name to check
@@ -77,4 +179,30 @@ pub enum Action {
         #[arg(long)]
         silent: bool,
     },
+
+    /// Edit the config file directly, rather than just reading it
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Set a field on a command entry (creating the command, and the
+    /// config file itself, if either doesn't exist yet)
+    Set {
+        /// Command name to set the field on
+        command: String,
+
+        /// Entry field name (e.g. `bind`, `share`, `enabled`)
+        key: String,
+
+        /// Value to set; for list fields (`bind`, `share`, `cap`, ...) this
+        /// is appended rather than replacing the whole list
+        value: String,
+    },
+
+    /// Open `$EDITOR` on the resolved config file
+    Edit,
 }
@@ -0,0 +1,47 @@
+// Copyright (C) 2025 Pierre Le Gall
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/// Levenshtein edit distance between two strings, using a single rolling
+/// row of length `b.len() + 1` rather than a full matrix.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b_chars.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev_diag = row[0];
+        row[0] = i + 1;
+
+        for (j, &cb) in b_chars.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            let above = row[j + 1];
+            let new_value = (row[j] + 1).min(above + 1).min(prev_diag + cost);
+            prev_diag = above;
+            row[j + 1] = new_value;
+        }
+    }
+
+    row[b_chars.len()]
+}
+
+/// Find the closest candidate to `target`, within a distance threshold
+/// proportional to the length of the longer string (capped at 3).
+pub fn closest_match<'a>(target: &str, candidates: impl IntoIterator<Item = &'a String>) -> Option<&'a str> {
+    let mut best: Option<(&str, usize)> = None;
+
+    for candidate in candidates {
+        let distance = levenshtein(target, candidate);
+        let threshold = (target.len().max(candidate.len()) / 3).min(3).max(1);
+        if distance > threshold {
+            continue;
+        }
+        let better = match best {
+            Some((_, best_distance)) => distance < best_distance,
+            None => true,
+        };
+        if better {
+            best = Some((candidate.as_str(), distance));
+        }
+    }
+
+    best.map(|(name, _)| name)
+}
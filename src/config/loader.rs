@@ -2,10 +2,15 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use super::Config;
+use super::{Config, ConfigError};
+
+/// Top-level key used for `%include`-style file inclusion.
+const INCLUDE_KEY: &str = "include";
 
 /// Local config file name
 const LOCAL_CONFIG_FILE_NAME: &str = ".shwrap.yaml";
@@ -16,6 +21,30 @@ const USER_CONFIG_FILE_NAME: &str = "default.yaml";
 /// User config directory path relative to HOME
 const USER_CONFIG_DIR_PATH: &str = "~/.config/shwrap";
 
+/// System-wide config directory, lowest-precedence layer
+const SYSTEM_CONFIG_DIR: &str = "/etc/shwrap";
+
+/// Environment variable holding a colon-separated list of config paths,
+/// merged on top of the discovered user/local configs (like
+/// `JJ_CONFIG`/`STARSHIP_CONFIG`). Highest precedence of any layer.
+const SHWRAP_CONFIG_ENV: &str = "SHWRAP_CONFIG";
+
+/// Basename (without extension) probed in the system/user discovery layers
+const DISCOVERY_CONFIG_BASENAME: &str = "config";
+
+/// Extensions checked for `DISCOVERY_CONFIG_BASENAME`; more than one present
+/// in the same directory is treated as an ambiguous source.
+const DISCOVERY_CONFIG_EXTENSIONS: [&str; 2] = ["yaml", "yml"];
+
+/// A named precedence layer in the system/user/project discovery chain,
+/// from lowest to highest priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigLayer {
+    System,
+    User,
+    Project,
+}
+
 pub struct ConfigLoader;
 
 impl ConfigLoader {
@@ -98,33 +127,226 @@ impl ConfigLoader {
         Ok(None)
     }
 
-    /// Load config from the found path
-    /// If both user and local configs exist, merge them (local overrides user)
-    pub fn load() -> Result<Option<Config>> {
-        let user_config = Self::get_user_config_file()?;
-        let local_config = Self::get_local_config_file()?;
-
-        match (user_config, local_config) {
-            (Some(user_path), Some(local_path)) => {
-                // Both exist: merge them (local overrides user)
-                let user = Config::from_file(&user_path)?;
-                let local = Config::from_file(&local_path)?;
-                Ok(Some(Config::merge(user, local)))
-            }
-            (Some(user_path), None) => {
-                // Only user config exists
-                let config = Config::from_file(&user_path)?;
-                Ok(Some(config))
-            }
-            (None, Some(local_path)) => {
-                // Only local config exists
-                let config = Config::from_file(&local_path)?;
-                Ok(Some(config))
+    /// Load a config file, resolving any top-level `include:` list before
+    /// applying the file's own entries. Included files are merged in the
+    /// order listed, with earlier includes as the parent layer; the
+    /// including file's own entries always win (it is merged in last).
+    /// Include paths are resolved relative to the including file and `~`
+    /// is expanded. Include cycles are rejected with an error.
+    pub fn load_file_with_includes<P: AsRef<Path>>(path: P) -> Result<Config> {
+        let mut visited = HashSet::new();
+        Self::load_file_with_includes_inner(path.as_ref(), &mut visited)
+    }
+
+    fn load_file_with_includes_inner(path: &Path, visited: &mut HashSet<PathBuf>) -> Result<Config> {
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve config file: {:?}", path))?;
+
+        if !visited.insert(canonical.clone()) {
+            return Err(ConfigError::Cycle {
+                kind: "include",
+                path: format!("{:?}", path),
             }
-            (None, None) => {
-                // No config exists
-                Ok(None)
+            .into());
+        }
+
+        let yaml = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+        let mut raw: serde_yaml::Value = serde_yaml::from_str(&yaml)
+            .with_context(|| format!("Failed to parse YAML config {:?}", path))?;
+
+        let includes = Self::take_includes(&mut raw);
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+        super::check_schema_version(&raw)
+            .with_context(|| format!("Invalid config file: {:?}", path))?;
+        super::validate_entries(&raw).with_context(|| format!("Invalid config file: {:?}", path))?;
+
+        let mut merged = Config {
+            version: None,
+            policy: None,
+            entries: HashMap::new(),
+        };
+        for include in includes {
+            let expanded = shellexpand::tilde(&include);
+            let include_path = base_dir.join(expanded.as_ref());
+            let included = Self::load_file_with_includes_inner(&include_path, visited)?;
+            merged = Config::merge(merged, included);
+        }
+
+        let own: Config = serde_yaml::from_value(raw)
+            .with_context(|| format!("Failed to parse YAML config {:?}", path))?;
+
+        // Allow the same file to be included again from a sibling branch
+        // (a diamond), just not from one of its own ancestors.
+        visited.remove(&canonical);
+
+        Ok(Config::merge(merged, own))
+    }
+
+    /// Pull the `include` key (string or list of strings) out of a raw YAML
+    /// mapping so the rest can be parsed as a normal `Config`.
+    fn take_includes(raw: &mut serde_yaml::Value) -> Vec<String> {
+        let Some(mapping) = raw.as_mapping_mut() else {
+            return Vec::new();
+        };
+
+        match mapping.remove(INCLUDE_KEY) {
+            Some(serde_yaml::Value::Sequence(items)) => items
+                .into_iter()
+                .filter_map(|item| item.as_str().map(String::from))
+                .collect(),
+            Some(serde_yaml::Value::String(single)) => vec![single],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Load each config layer that exists, in merge order (user, then
+    /// local, then each `SHWRAP_CONFIG` path left-to-right), without
+    /// merging them, each tagged with the `Source` it came from. Useful
+    /// for anything that needs to know which file (or, after
+    /// `merge_with_template`, which model) a given field ultimately came
+    /// from (e.g. `sheld explain`, `--show-origin`).
+    pub fn load_layers() -> Result<Vec<(super::Source, PathBuf, Config)>> {
+        let mut layers = Vec::new();
+
+        if let Some(user_path) = Self::get_user_config_file()? {
+            let config = Self::load_file_with_includes(&user_path)?;
+            layers.push((super::Source::User, user_path, config));
+        }
+
+        if let Some(local_path) = Self::get_local_config_file()? {
+            let config = Self::load_file_with_includes(&local_path)?;
+            layers.push((super::Source::Local, local_path, config));
+        }
+
+        for path in Self::shwrap_config_paths() {
+            let config = Self::load_file_with_includes(&path)?;
+            layers.push((super::Source::Env, path, config));
+        }
+
+        Ok(layers)
+    }
+
+    /// Find the single `<basename>.{yaml,yml}` candidate in `dir`. Returns
+    /// an "ambiguous source" error naming both paths if more than one
+    /// equally-ranked candidate exists, rather than silently picking one.
+    fn find_unambiguous(dir: &Path, basename: &str) -> Result<Option<PathBuf>> {
+        let candidates: Vec<PathBuf> = DISCOVERY_CONFIG_EXTENSIONS
+            .iter()
+            .map(|ext| dir.join(format!("{}.{}", basename, ext)))
+            .filter(|path| path.exists())
+            .collect();
+
+        match candidates.as_slice() {
+            [] => Ok(None),
+            [single] => Ok(Some(single.clone())),
+            _ => {
+                let candidates = candidates
+                    .iter()
+                    .map(|p| format!("{:?}", p))
+                    .collect::<Vec<_>>()
+                    .join(" and ");
+                Err(ConfigError::AmbiguousSource {
+                    dir: format!("{:?}", dir),
+                    candidates,
+                }
+                .into())
             }
         }
     }
+
+    /// Discover config layers across system (`/etc/shwrap`), user
+    /// (`$XDG_CONFIG_HOME`-style dir) and project (walked up from cwd)
+    /// locations, in precedence order (most specific last). Each layer's
+    /// path is returned alongside its parsed config so callers can report
+    /// where a given entry ultimately came from.
+    pub fn discover_layers() -> Result<Vec<(ConfigLayer, PathBuf, Config)>> {
+        let mut layers = Vec::new();
+
+        if let Some(path) =
+            Self::find_unambiguous(Path::new(SYSTEM_CONFIG_DIR), DISCOVERY_CONFIG_BASENAME)?
+        {
+            let config = Self::load_file_with_includes(&path)?;
+            layers.push((ConfigLayer::System, path, config));
+        }
+
+        let user_dir = Self::get_user_config_dir();
+        if let Some(path) = Self::find_unambiguous(&user_dir, DISCOVERY_CONFIG_BASENAME)? {
+            let config = Self::load_file_with_includes(&path)?;
+            layers.push((ConfigLayer::User, path, config));
+        } else if let Some(path) = Self::get_user_config_file()? {
+            // Fall back to the legacy `default.yaml` location.
+            let config = Self::load_file_with_includes(&path)?;
+            layers.push((ConfigLayer::User, path, config));
+        }
+
+        if let Some(path) = Self::get_local_config_file()? {
+            let config = Self::load_file_with_includes(&path)?;
+            layers.push((ConfigLayer::Project, path, config));
+        }
+
+        Ok(layers)
+    }
+
+    /// Discover and merge every layer left-to-right, so the most specific
+    /// layer (project) wins, while each entry's own `override` flag still
+    /// governs deep-merge vs. full replacement.
+    pub fn discover() -> Result<Option<Config>> {
+        let layers = Self::discover_layers()?
+            .into_iter()
+            .map(|(layer, _, config)| (layer.into(), config))
+            .collect();
+        Ok(Config::merge_layers(layers))
+    }
+
+    /// Parse `SHWRAP_CONFIG` into the list of paths it names, in the order
+    /// given (`~` expanded, empty segments skipped so a stray leading or
+    /// trailing `:` is harmless). Returns an empty list if the variable is
+    /// unset.
+    fn shwrap_config_paths() -> Vec<PathBuf> {
+        let Ok(value) = env::var(SHWRAP_CONFIG_ENV) else {
+            return Vec::new();
+        };
+
+        value
+            .split(':')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| PathBuf::from(shellexpand::tilde(segment).as_ref()))
+            .collect()
+    }
+
+    /// Load config from the found path. The deterministic, lowest-to-highest
+    /// precedence order is: user config, then local config, then each path
+    /// named in `SHWRAP_CONFIG` (left-to-right, so later entries win), with
+    /// later layers merged on top of earlier ones via `Config::merge` (each
+    /// entry's own `override` flag still governs deep-merge vs. full
+    /// replacement). This lets a user layer a shared team config plus a
+    /// personal overlay on top of the usual user/local hierarchy.
+    pub fn load() -> Result<Option<Config>> {
+        let mut layers = Vec::new();
+
+        if let Some(user_path) = Self::get_user_config_file()? {
+            layers.push((super::LayerKind::User, Self::load_file_with_includes(&user_path)?));
+        }
+        if let Some(local_path) = Self::get_local_config_file()? {
+            layers.push((super::LayerKind::LocalRepo, Self::load_file_with_includes(&local_path)?));
+        }
+        for path in Self::shwrap_config_paths() {
+            layers.push((super::LayerKind::Env, Self::load_file_with_includes(&path)?));
+        }
+
+        Ok(Config::merge_layers(layers))
+    }
+}
+
+impl From<ConfigLayer> for super::LayerKind {
+    fn from(layer: ConfigLayer) -> Self {
+        match layer {
+            ConfigLayer::System => super::LayerKind::Global,
+            ConfigLayer::User => super::LayerKind::User,
+            ConfigLayer::Project => super::LayerKind::LocalRepo,
+        }
+    }
 }
@@ -1,10 +1,12 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Deserializer, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
+use thiserror::Error;
 
 pub mod loader;
+pub mod oci;
 
 /// Custom deserializer for extends field that accepts both String and Vec<String>
 fn deserialize_extends<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
@@ -54,15 +56,257 @@ where
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
+    /// Schema version the config was written against. Checked against
+    /// `CURRENT_SCHEMA_VERSION` at load time so a config written for a
+    /// future, incompatible schema is rejected instead of misparsed.
+    #[serde(default)]
+    pub version: Option<u64>,
+    /// Optional allow/deny rules a resolved entry's `share`/`bind`/`ro_bind`
+    /// must pass, independent of anything the entry itself declares.
+    #[serde(default)]
+    pub policy: Option<PolicyConfig>,
     #[serde(flatten)]
     pub entries: HashMap<String, Entry>,
 }
 
+/// Schema version this build understands. Bump when making a breaking
+/// change to the config format.
+const CURRENT_SCHEMA_VERSION: u64 = 1;
+
+/// Field names accepted on an `Entry` mapping (including its serde
+/// renames). Kept in sync by hand: `deny_unknown_fields` can't be combined
+/// with `Config.entries`'s `#[serde(flatten)]`, so unknown keys are caught
+/// here instead, by walking the raw YAML before typed deserialization.
+const ENTRY_FIELDS: &[&str] = &[
+    "type",
+    "enabled",
+    "override",
+    "extends",
+    "share",
+    "bind",
+    "ro_bind",
+    "dev_bind",
+    "bind_try",
+    "ro_bind_try",
+    "dev_bind_try",
+    "tmpfs",
+    "chdir",
+    "die_with_parent",
+    "new_session",
+    "cap",
+    "cap_drop",
+    "env",
+    "unset_env",
+    "seccomp",
+    "resources",
+    "profiles",
+    "alias",
+    "args",
+];
+
+/// Reject a config declaring a `version:` newer than this build understands.
+fn check_schema_version(raw: &serde_yaml::Value) -> Result<()> {
+    let Some(mapping) = raw.as_mapping() else {
+        return Ok(());
+    };
+    let Some(version_value) = mapping.get(serde_yaml::Value::String("version".to_string())) else {
+        return Ok(());
+    };
+
+    let version: u64 = serde_yaml::from_value(version_value.clone())
+        .context("Config `version` field must be a non-negative integer")?;
+
+    if version > CURRENT_SCHEMA_VERSION {
+        bail!(
+            "Config declares schema version {} but this build only understands up to version {}",
+            version,
+            CURRENT_SCHEMA_VERSION
+        );
+    }
+
+    Ok(())
+}
+
+/// Walk every entry (and, recursively, every named profile within it) in
+/// the raw YAML and reject any key that isn't a known `Entry` field, e.g. a
+/// typo like `shre:` or `ro_binds:` that `serde`'s default behavior would
+/// otherwise silently drop, quietly under-restricting the sandbox.
+fn validate_entries(raw: &serde_yaml::Value) -> Result<()> {
+    let Some(mapping) = raw.as_mapping() else {
+        return Ok(());
+    };
+
+    for (key, value) in mapping {
+        let Some(name) = key.as_str() else { continue };
+        if name == "version" {
+            continue;
+        }
+        if name == "policy" {
+            validate_policy_fields(value)?;
+            continue;
+        }
+        validate_entry_fields(name, value)?;
+        validate_entry_types(name, value)?;
+    }
+
+    Ok(())
+}
+
+/// Values accepted for an entry's `type:` field (including its `profiles`).
+const ENTRY_TYPES: &[&str] = &["command", "model", "alias"];
+
+/// Walk the same raw YAML as `validate_entry_fields`, checking `type:`
+/// against `ENTRY_TYPES` before typed deserialization, so an invalid value
+/// (e.g. a typo like `type: comand`) gets a `ConfigError::UnknownType`
+/// instead of serde's generic "unknown variant" error.
+fn validate_entry_types(entry_name: &str, value: &serde_yaml::Value) -> Result<(), ConfigError> {
+    let Some(mapping) = value.as_mapping() else {
+        return Ok(());
+    };
+
+    if let Some(type_value) = mapping.get(serde_yaml::Value::String("type".to_string())) {
+        if let Some(type_name) = type_value.as_str() {
+            if !ENTRY_TYPES.contains(&type_name) {
+                return Err(ConfigError::UnknownType {
+                    entry: entry_name.to_string(),
+                    type_name: type_name.to_string(),
+                });
+            }
+        }
+    }
+
+    if let Some(profiles) = mapping
+        .get(serde_yaml::Value::String("profiles".to_string()))
+        .and_then(|v| v.as_mapping())
+    {
+        for (profile_name, profile_value) in profiles {
+            if let Some(profile_name) = profile_name.as_str() {
+                let qualified = format!("{}.profiles.{}", entry_name, profile_name);
+                validate_entry_types(&qualified, profile_value)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Structured errors for config loading and entry lookup, so callers can
+/// tell *why* something failed (malformed YAML, an ambiguous source, an
+/// unknown `type`, a cycle, ...) instead of everything collapsing into one
+/// `anyhow::Error` string, or a lookup silently returning `None` for both
+/// "missing" and "malformed". Call sites that only want the old pass/fail
+/// behavior can still use `?` into `anyhow::Result`, since `anyhow::Error`
+/// has a blanket `From` for any `std::error::Error`.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to parse YAML config: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("Ambiguous config source in {dir}: found both {candidates}")]
+    AmbiguousSource { dir: String, candidates: String },
+
+    #[error("Unknown type '{type_name}' in entry '{entry}'")]
+    UnknownType { entry: String, type_name: String },
+
+    #[error("Cycle detected while resolving {kind}: {path}")]
+    Cycle { kind: &'static str, path: String },
+
+    #[error("entry '{0}' not found in configuration")]
+    NotFound(String),
+
+    #[error("entry '{name}' is a {found}, not a {expected}")]
+    WrongType {
+        name: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+/// Converts a `ConfigError::NotFound` into a plain `Ok(None)`, leaving any
+/// other variant (wrong type, a cycle, ...) to keep propagating as `Err`
+/// instead of being silently folded into the same `None`.
+pub trait ConfigResultExt<T> {
+    fn not_found_is_none(self) -> Result<Option<T>, ConfigError>;
+}
+
+impl<T> ConfigResultExt<T> for Result<T, ConfigError> {
+    fn not_found_is_none(self) -> Result<Option<T>, ConfigError> {
+        match self {
+            Ok(value) => Ok(Some(value)),
+            Err(ConfigError::NotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Field names accepted on a `policy:` mapping and on each of its `rules`.
+const POLICY_FIELDS: &[&str] = &["on_violation", "rules"];
+const POLICY_RULE_FIELDS: &[&str] = &["effect", "share", "bind"];
+
+/// Same unknown-field check as `validate_entry_fields`, for the top-level
+/// `policy:` section and its `rules` list.
+fn validate_policy_fields(value: &serde_yaml::Value) -> Result<()> {
+    let Some(mapping) = value.as_mapping() else {
+        return Ok(());
+    };
+
+    for (key, field_value) in mapping {
+        let Some(field) = key.as_str() else { continue };
+        if !POLICY_FIELDS.contains(&field) {
+            bail!("Unknown field '{}' in policy", field);
+        }
+
+        if field == "rules" {
+            if let Some(rules) = field_value.as_sequence() {
+                for rule in rules {
+                    let Some(rule_mapping) = rule.as_mapping() else { continue };
+                    for (rule_key, _) in rule_mapping {
+                        let Some(rule_field) = rule_key.as_str() else { continue };
+                        if !POLICY_RULE_FIELDS.contains(&rule_field) {
+                            bail!("Unknown field '{}' in policy rule", rule_field);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_entry_fields(entry_name: &str, value: &serde_yaml::Value) -> Result<()> {
+    let Some(mapping) = value.as_mapping() else {
+        return Ok(());
+    };
+
+    for (key, field_value) in mapping {
+        let Some(field) = key.as_str() else { continue };
+        if !ENTRY_FIELDS.contains(&field) {
+            bail!("Unknown field '{}' in entry '{}'", field, entry_name);
+        }
+
+        if field == "profiles" {
+            if let Some(profiles) = field_value.as_mapping() {
+                for (profile_name, profile_value) in profiles {
+                    if let Some(profile_name) = profile_name.as_str() {
+                        let qualified = format!("{}.profiles.{}", entry_name, profile_name);
+                        validate_entry_fields(&qualified, profile_value)?;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum EntryType {
     Command,
     Model,
+    /// A short name that resolves to another command entry (see `Entry::alias`).
+    Alias,
 }
 
 impl Default for EntryType {
@@ -71,6 +315,178 @@ impl Default for EntryType {
     }
 }
 
+/// Label used in `ConfigError::WrongType` messages.
+fn entry_type_label(entry_type: &EntryType) -> &'static str {
+    match entry_type {
+        EntryType::Command => "command",
+        EntryType::Model => "model",
+        EntryType::Alias => "alias",
+    }
+}
+
+/// Whether a `PolicyRule` permits or forbids what it matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyEffect {
+    Allow,
+    Deny,
+}
+
+/// What happens to an entry that fails policy, configured via the
+/// policy's `on_violation` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    /// Fail the whole command with a `PolicyViolation` error.
+    Reject,
+    /// Drop the offending `share`/`bind`/`ro_bind` value(s) and continue.
+    Strip,
+}
+
+impl Default for PolicyAction {
+    fn default() -> Self {
+        PolicyAction::Reject
+    }
+}
+
+/// A single allow/deny rule in a `policy:` section. `share` matches a
+/// resolved entry's `share` list exactly; `bind` matches as a prefix
+/// against the host-side path of each resolved `bind`/`ro_bind` entry
+/// (the part before the `:`). Set whichever one this rule is about.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub effect: PolicyEffect,
+    #[serde(default)]
+    pub share: Option<String>,
+    #[serde(default)]
+    pub bind: Option<String>,
+}
+
+/// The top-level `policy:` section: a Casbin-style rule list evaluated
+/// after `merge_with_template`, independent of what any entry (or model it
+/// extends) declares. An explicit `deny` always wins over an `allow`, so a
+/// base/org-level policy's deny rules can't be escalated past by a
+/// higher-priority user or project config (see `Config::merge`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    #[serde(default)]
+    pub on_violation: Option<PolicyAction>,
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+}
+
+/// Which resolved field of an entry a `PolicyViolation` was raised against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyField {
+    Share,
+    Bind,
+    RoBind,
+}
+
+impl std::fmt::Display for PolicyField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PolicyField::Share => "share",
+            PolicyField::Bind => "bind",
+            PolicyField::RoBind => "ro_bind",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The resolved `share`/`bind`/`ro_bind` value an explicit `deny` rule in
+/// the active policy matched, and which rule (1-indexed, for the error
+/// message) matched it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyViolation {
+    pub field: PolicyField,
+    pub value: String,
+    pub rule_number: usize,
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "policy rule #{} denies {} '{}'",
+            self.rule_number, self.field, self.value
+        )
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+/// Action an OCI-style seccomp filter takes for a matched syscall, spelled
+/// the way `SCMP_ACT_*` constants are in an OCI runtime spec `seccomp`
+/// section (so a profile can be copied in verbatim).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeccompAction {
+    #[serde(rename = "SCMP_ACT_ALLOW")]
+    Allow,
+    #[serde(rename = "SCMP_ACT_ERRNO")]
+    Errno,
+    #[serde(rename = "SCMP_ACT_KILL")]
+    Kill,
+    #[serde(rename = "SCMP_ACT_TRAP")]
+    Trap,
+    #[serde(rename = "SCMP_ACT_LOG")]
+    Log,
+}
+
+impl std::fmt::Display for SeccompAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SeccompAction::Allow => "SCMP_ACT_ALLOW",
+            SeccompAction::Errno => "SCMP_ACT_ERRNO",
+            SeccompAction::Kill => "SCMP_ACT_KILL",
+            SeccompAction::Trap => "SCMP_ACT_TRAP",
+            SeccompAction::Log => "SCMP_ACT_LOG",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One syscall rule within a `seccomp` profile: `action` applies to every
+/// syscall named in `names`, overriding the profile's `default_action`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeccompRule {
+    pub names: Vec<String>,
+    pub action: SeccompAction,
+}
+
+/// An OCI-style seccomp profile: syscalls not matched by any `rules` entry
+/// fall back to `default_action`. Compiled to a BPF program and handed to
+/// bwrap as `--seccomp <fd>` by `bwrap::WrappedCommandBuilder`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeccompProfile {
+    #[serde(rename = "defaultAction")]
+    pub default_action: SeccompAction,
+    #[serde(default)]
+    pub rules: Vec<SeccompRule>,
+}
+
+/// Cgroup v2 resource limits to place the sandboxed process under.
+/// `None` fields are left unmanaged (that controller's file is never
+/// written, so the process just inherits the parent cgroup's limit); a
+/// `Some` field is written verbatim to its `<controller>.max` file by
+/// `bwrap::cgroup::create_delegated_subtree`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// `memory.max`, in bytes.
+    #[serde(default)]
+    pub memory_max: Option<u64>,
+    /// The quota half of `cpu.max`, in microseconds per period.
+    #[serde(default)]
+    pub cpu_quota: Option<u64>,
+    /// The period half of `cpu.max`, in microseconds. Defaults to the
+    /// kernel's own default of 100000 when a quota is set without one.
+    #[serde(default)]
+    pub cpu_period: Option<u64>,
+    /// `pids.max`, the maximum number of tasks the cgroup may contain.
+    #[serde(default)]
+    pub pids_max: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Entry {
     #[serde(default, rename = "type")]
@@ -105,10 +521,105 @@ pub struct Entry {
     pub new_session: bool,
     #[serde(default)]
     pub cap: Vec<String>,
+    /// Capabilities to drop from the sandboxed process's bounding set,
+    /// emitted as `--cap-drop`. The special `ALL` token drops every
+    /// capability, matching the bounding-set convention other container
+    /// runtimes use for "drop everything, then re-add a minimal set with
+    /// `cap`". Validated against the known `CAP_*` set (plus `ALL`) by
+    /// `bwrap::WrappedCommandBuilder::new`.
+    #[serde(default)]
+    pub cap_drop: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub unset_env: Vec<String>,
+    /// An OCI-style seccomp profile restricting which syscalls the sandboxed
+    /// process may make. Compiled to BPF and handed to bwrap as `--seccomp
+    /// <fd>` by `bwrap::WrappedCommandBuilder::exec`; like `chdir`, this is
+    /// a scalar field that the command's own value overrides outright
+    /// rather than merging with an extended model's.
+    #[serde(default)]
+    pub seccomp: Option<SeccompProfile>,
+    /// Cgroup v2 CPU/memory/pids limits placed on the sandboxed process by
+    /// `bwrap::WrappedCommandBuilder::exec`. Same scalar-field convention
+    /// as `seccomp`: the command's own value overrides an extended
+    /// model's outright rather than merging field-by-field.
+    #[serde(default)]
+    pub resources: Option<ResourceLimits>,
+    /// Named variants of this entry (e.g. `dev`, `ci`, `offline`) applied
+    /// on top of the base entry when selected, so a command doesn't need
+    /// to be duplicated just to toggle a handful of fields. See
+    /// `ProfileOverlay` for why this isn't just another `Entry`.
+    #[serde(default)]
+    pub profiles: HashMap<String, ProfileOverlay>,
+    /// For `type: alias` entries, the command (or alias) entry this one
+    /// resolves to. Ignored otherwise.
+    #[serde(default)]
+    pub alias: Option<String>,
+    /// For `type: alias` entries, fixed extra arguments appended ahead of
+    /// whatever arguments are passed at invocation time. Ignored otherwise.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// A named variant under an `Entry`'s `profiles:` map (e.g. `dev`, `ci`,
+/// `offline`), applied on top of the base entry by
+/// `Config::get_command_with_profile`/`Entry::apply_profile`.
+///
+/// This is deliberately not just another `Entry`: `Entry`'s scalar fields
+/// (`enabled`, `override`, `die_with_parent`, `new_session`) all have
+/// `#[serde(default = ...)]` fallbacks, so a profile that only restates
+/// `env` would deserialize with those fields at their struct defaults
+/// (`enabled: true`, `die_with_parent: false`, `new_session: false`) —
+/// indistinguishable from the profile actually setting them. Merging that
+/// with `Entry::deep_merge`'s "child wins" scalar rule would then silently
+/// re-enable a disabled command and strip `die_with_parent`/`new_session`
+/// just by selecting an unrelated profile, weakening the sandbox. Keeping
+/// these as `Option<bool>` (`None` when the profile doesn't mention the
+/// field at all) lets `apply_profile` fall back to the base entry's value
+/// instead.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileOverlay {
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default, rename = "override")]
+    pub override_parent: Option<bool>,
+    #[serde(default)]
+    pub share: Vec<String>,
+    #[serde(default)]
+    pub bind: Vec<String>,
+    #[serde(default)]
+    pub ro_bind: Vec<String>,
+    #[serde(default)]
+    pub dev_bind: Vec<String>,
+    #[serde(default)]
+    pub bind_try: Vec<String>,
+    #[serde(default)]
+    pub ro_bind_try: Vec<String>,
+    #[serde(default)]
+    pub dev_bind_try: Vec<String>,
+    #[serde(default)]
+    pub tmpfs: Vec<String>,
+    #[serde(default)]
+    pub chdir: Option<String>,
+    #[serde(default)]
+    pub die_with_parent: Option<bool>,
+    #[serde(default)]
+    pub new_session: Option<bool>,
+    #[serde(default)]
+    pub cap: Vec<String>,
+    #[serde(default)]
+    pub cap_drop: Vec<String>,
     #[serde(default)]
     pub env: HashMap<String, String>,
     #[serde(default)]
     pub unset_env: Vec<String>,
+    #[serde(default)]
+    pub seccomp: Option<SeccompProfile>,
+    #[serde(default)]
+    pub resources: Option<ResourceLimits>,
+    #[serde(default)]
+    pub args: Vec<String>,
 }
 
 fn default_enabled() -> bool {
@@ -127,6 +638,40 @@ fn default_new_session() -> bool {
     false
 }
 
+/// The 1-indexed position of the first `deny` rule matching `matches`, for
+/// `Config::check_policy`'s error message (`None` if no deny rule matches).
+fn first_deny_match(rules: &[PolicyRule], matches: impl Fn(&PolicyRule) -> bool) -> Option<usize> {
+    rules
+        .iter()
+        .enumerate()
+        .find(|(_, rule)| rule.effect == PolicyEffect::Deny && matches(rule))
+        .map(|(index, _)| index + 1)
+}
+
+/// Combine a parent and child `policy:` section: rules concatenate
+/// (parent's first) rather than the child replacing the parent outright,
+/// so a base/org policy's deny rules stay in effect no matter what a
+/// higher-priority layer's policy adds. `on_violation` follows the usual
+/// "child wins if set" scalar-field convention.
+fn merge_policy(parent: Option<PolicyConfig>, child: Option<PolicyConfig>) -> Option<PolicyConfig> {
+    match (parent, child) {
+        (None, None) => None,
+        (Some(policy), None) | (None, Some(policy)) => Some(policy),
+        (Some(parent), Some(child)) => Some(PolicyConfig {
+            on_violation: child.on_violation.or(parent.on_violation),
+            rules: parent.rules.into_iter().chain(child.rules).collect(),
+        }),
+    }
+}
+
+/// Parse a `config set` value as a bool, naming the offending field in the
+/// error so a typo like `config set vim enabled maybe` is diagnosable.
+fn parse_field_bool(field: &str, value: &str) -> Result<bool> {
+    value
+        .parse()
+        .with_context(|| format!("Field '{}' expects true/false, got '{}'", field, value))
+}
+
 /// Deduplicate a vector, preserving order (first occurrence kept)
 fn deduplicate_vec(vec: Vec<String>) -> Vec<String> {
     let mut seen = std::collections::HashSet::new();
@@ -135,7 +680,124 @@ fn deduplicate_vec(vec: Vec<String>) -> Vec<String> {
         .collect()
 }
 
+
+/// Apply `!`-prefixed subtractive entries: a `!`-prefixed token removes any
+/// matching non-prefixed item (and itself) from the array. Lets a command
+/// drop something a parent layer or extended model introduced, e.g.
+/// `share: ["!network"]`. A token that matches nothing is a no-op.
+fn apply_removals(vec: Vec<String>) -> Vec<String> {
+    let removals: std::collections::HashSet<&str> = vec
+        .iter()
+        .filter_map(|item| item.strip_prefix('!'))
+        .collect();
+
+    if removals.is_empty() {
+        return vec;
+    }
+
+    vec.into_iter()
+        .filter(|item| {
+            let bare = item.strip_prefix('!').unwrap_or(item);
+            !removals.contains(bare)
+        })
+        .collect()
+}
+
+/// An `Entry` with every field at its empty/default value, used as the
+/// accumulator when flattening a chain of `extends` ancestors.
+fn blank_model_entry() -> Entry {
+    Entry {
+        entry_type: EntryType::default(),
+        enabled: default_enabled(),
+        override_parent: default_override(),
+        extends: vec![],
+        share: vec![],
+        bind: vec![],
+        ro_bind: vec![],
+        dev_bind: vec![],
+        bind_try: vec![],
+        ro_bind_try: vec![],
+        dev_bind_try: vec![],
+        tmpfs: vec![],
+        chdir: None,
+        die_with_parent: default_die_with_parent(),
+        new_session: default_new_session(),
+        cap: vec![],
+        cap_drop: vec![],
+        env: HashMap::new(),
+        unset_env: vec![],
+        seccomp: None,
+        resources: None,
+        profiles: HashMap::new(),
+        alias: None,
+        args: vec![],
+    }
+}
+
 impl Entry {
+    /// A fresh `type: command` entry with every field at its default,
+    /// for `sheld config set` to seed a command name it hasn't seen before.
+    pub fn new_command() -> Entry {
+        blank_model_entry()
+    }
+
+    /// Set a single field on this entry by its YAML key name, for `sheld
+    /// config set`. List-valued fields (`bind`, `share`, `cap`, ...) append
+    /// `value` rather than replacing the whole list, so running `config
+    /// set` repeatedly reads naturally as "add this one thing". Boolean and
+    /// scalar fields (`enabled`, `chdir`, ...) are replaced outright. `env`
+    /// expects `value` in `KEY=VALUE` form. Structured fields (`seccomp`,
+    /// `resources`, `profiles`) aren't settable this way since there's no
+    /// single string that captures them; edit the YAML directly for those.
+    pub fn set_field(&mut self, key: &str, value: &str) -> Result<()> {
+        if !ENTRY_FIELDS.contains(&key) {
+            bail!("Unknown field '{}'", key);
+        }
+
+        match key {
+            "type" => {
+                self.entry_type = match value {
+                    "command" => EntryType::Command,
+                    "model" => EntryType::Model,
+                    "alias" => EntryType::Alias,
+                    _ => bail!("Unknown type '{}', expected one of: {}", value, ENTRY_TYPES.join(", ")),
+                }
+            }
+            "enabled" => self.enabled = parse_field_bool(key, value)?,
+            "override" => self.override_parent = parse_field_bool(key, value)?,
+            "extends" => self.extends.push(value.to_string()),
+            "share" => self.share.push(value.to_string()),
+            "bind" => self.bind.push(value.to_string()),
+            "ro_bind" => self.ro_bind.push(value.to_string()),
+            "dev_bind" => self.dev_bind.push(value.to_string()),
+            "bind_try" => self.bind_try.push(value.to_string()),
+            "ro_bind_try" => self.ro_bind_try.push(value.to_string()),
+            "dev_bind_try" => self.dev_bind_try.push(value.to_string()),
+            "tmpfs" => self.tmpfs.push(value.to_string()),
+            "chdir" => self.chdir = Some(value.to_string()),
+            "die_with_parent" => self.die_with_parent = parse_field_bool(key, value)?,
+            "new_session" => self.new_session = parse_field_bool(key, value)?,
+            "cap" => self.cap.push(value.to_string()),
+            "cap_drop" => self.cap_drop.push(value.to_string()),
+            "env" => {
+                let (env_key, env_value) = value
+                    .split_once('=')
+                    .with_context(|| format!("env value '{}' must be in KEY=VALUE form", value))?;
+                self.env.insert(env_key.to_string(), env_value.to_string());
+            }
+            "unset_env" => self.unset_env.push(value.to_string()),
+            "alias" => self.alias = Some(value.to_string()),
+            "args" => self.args.push(value.to_string()),
+            "seccomp" | "resources" | "profiles" => bail!(
+                "Field '{}' has a nested structure; edit the YAML file directly instead of `config set`",
+                key
+            ),
+            _ => bail!("Field '{}' isn't settable via `config set`", key),
+        }
+
+        Ok(())
+    }
+
     /// Deep merge parent and child entries
     /// - Arrays: parent items first, then unique child items (deduplicated)
     /// - env HashMap: parent + child, child wins on conflicts
@@ -148,7 +810,7 @@ impl Entry {
         let merged_share = if child.share.is_empty() {
             parent.share
         } else {
-            deduplicate_vec(merged_share)
+            apply_removals(deduplicate_vec(merged_share))
         };
 
         let mut merged_bind = parent.bind.clone();
@@ -156,7 +818,7 @@ impl Entry {
         let merged_bind = if child.bind.is_empty() {
             parent.bind
         } else {
-            deduplicate_vec(merged_bind)
+            apply_removals(deduplicate_vec(merged_bind))
         };
 
         let mut merged_ro_bind = parent.ro_bind.clone();
@@ -164,7 +826,7 @@ impl Entry {
         let merged_ro_bind = if child.ro_bind.is_empty() {
             parent.ro_bind
         } else {
-            deduplicate_vec(merged_ro_bind)
+            apply_removals(deduplicate_vec(merged_ro_bind))
         };
 
         let mut merged_dev_bind = parent.dev_bind.clone();
@@ -172,7 +834,7 @@ impl Entry {
         let merged_dev_bind = if child.dev_bind.is_empty() {
             parent.dev_bind
         } else {
-            deduplicate_vec(merged_dev_bind)
+            apply_removals(deduplicate_vec(merged_dev_bind))
         };
 
         let mut merged_tmpfs = parent.tmpfs.clone();
@@ -180,7 +842,7 @@ impl Entry {
         let merged_tmpfs = if child.tmpfs.is_empty() {
             parent.tmpfs
         } else {
-            deduplicate_vec(merged_tmpfs)
+            apply_removals(deduplicate_vec(merged_tmpfs))
         };
 
         let mut merged_unset_env = parent.unset_env.clone();
@@ -188,7 +850,7 @@ impl Entry {
         let merged_unset_env = if child.unset_env.is_empty() {
             parent.unset_env
         } else {
-            deduplicate_vec(merged_unset_env)
+            apply_removals(deduplicate_vec(merged_unset_env))
         };
 
         // Merge env: parent + child, child wins on conflicts
@@ -201,7 +863,7 @@ impl Entry {
         let merged_bind_try = if child.bind_try.is_empty() {
             parent.bind_try
         } else {
-            deduplicate_vec(merged_bind_try)
+            apply_removals(deduplicate_vec(merged_bind_try))
         };
 
         let mut merged_ro_bind_try = parent.ro_bind_try.clone();
@@ -209,7 +871,7 @@ impl Entry {
         let merged_ro_bind_try = if child.ro_bind_try.is_empty() {
             parent.ro_bind_try
         } else {
-            deduplicate_vec(merged_ro_bind_try)
+            apply_removals(deduplicate_vec(merged_ro_bind_try))
         };
 
         let mut merged_dev_bind_try = parent.dev_bind_try.clone();
@@ -217,7 +879,7 @@ impl Entry {
         let merged_dev_bind_try = if child.dev_bind_try.is_empty() {
             parent.dev_bind_try
         } else {
-            deduplicate_vec(merged_dev_bind_try)
+            apply_removals(deduplicate_vec(merged_dev_bind_try))
         };
 
         // Merge cap
@@ -226,10 +888,22 @@ impl Entry {
         let merged_cap = if child.cap.is_empty() {
             parent.cap
         } else {
-            deduplicate_vec(merged_cap)
+            apply_removals(deduplicate_vec(merged_cap))
+        };
+
+        let mut merged_cap_drop = parent.cap_drop.clone();
+        merged_cap_drop.extend(child.cap_drop.clone());
+        let merged_cap_drop = if child.cap_drop.is_empty() {
+            parent.cap_drop
+        } else {
+            apply_removals(deduplicate_vec(merged_cap_drop))
         };
 
-        // Scalar fields: child wins (including chdir, die_with_parent, new_session)
+        // Merge profiles: parent + child, child wins on name conflicts
+        let mut merged_profiles = parent.profiles.clone();
+        merged_profiles.extend(child.profiles);
+
+        // Scalar fields: child wins (including chdir, seccomp, resources, die_with_parent, new_session)
         Entry {
             entry_type: child.entry_type,
             enabled: child.enabled,
@@ -247,15 +921,275 @@ impl Entry {
             die_with_parent: child.die_with_parent,
             new_session: child.new_session,
             cap: merged_cap,
+            cap_drop: merged_cap_drop,
+            env: merged_env,
+            unset_env: merged_unset_env,
+            seccomp: child.seccomp.or(parent.seccomp),
+            resources: child.resources.or(parent.resources),
+            profiles: merged_profiles,
+            alias: child.alias.or(parent.alias),
+            args: if child.args.is_empty() { parent.args } else { child.args },
+        }
+    }
+
+    /// Apply a `profiles:` overlay on top of `base`, the way
+    /// `Config::get_command_with_profile` does. Arrays concatenate and
+    /// `!`-removals apply just like `deep_merge`, but scalar fields follow
+    /// `ProfileOverlay`'s "only override what was actually set" rule
+    /// instead of `deep_merge`'s unconditional "child wins" — see
+    /// `ProfileOverlay`'s doc comment for why.
+    pub fn apply_profile(base: Entry, overlay: ProfileOverlay) -> Entry {
+        let mut merged_share = base.share.clone();
+        merged_share.extend(overlay.share.clone());
+        let merged_share = if overlay.share.is_empty() {
+            base.share
+        } else {
+            apply_removals(deduplicate_vec(merged_share))
+        };
+
+        let mut merged_bind = base.bind.clone();
+        merged_bind.extend(overlay.bind.clone());
+        let merged_bind = if overlay.bind.is_empty() {
+            base.bind
+        } else {
+            apply_removals(deduplicate_vec(merged_bind))
+        };
+
+        let mut merged_ro_bind = base.ro_bind.clone();
+        merged_ro_bind.extend(overlay.ro_bind.clone());
+        let merged_ro_bind = if overlay.ro_bind.is_empty() {
+            base.ro_bind
+        } else {
+            apply_removals(deduplicate_vec(merged_ro_bind))
+        };
+
+        let mut merged_dev_bind = base.dev_bind.clone();
+        merged_dev_bind.extend(overlay.dev_bind.clone());
+        let merged_dev_bind = if overlay.dev_bind.is_empty() {
+            base.dev_bind
+        } else {
+            apply_removals(deduplicate_vec(merged_dev_bind))
+        };
+
+        let mut merged_bind_try = base.bind_try.clone();
+        merged_bind_try.extend(overlay.bind_try.clone());
+        let merged_bind_try = if overlay.bind_try.is_empty() {
+            base.bind_try
+        } else {
+            apply_removals(deduplicate_vec(merged_bind_try))
+        };
+
+        let mut merged_ro_bind_try = base.ro_bind_try.clone();
+        merged_ro_bind_try.extend(overlay.ro_bind_try.clone());
+        let merged_ro_bind_try = if overlay.ro_bind_try.is_empty() {
+            base.ro_bind_try
+        } else {
+            apply_removals(deduplicate_vec(merged_ro_bind_try))
+        };
+
+        let mut merged_dev_bind_try = base.dev_bind_try.clone();
+        merged_dev_bind_try.extend(overlay.dev_bind_try.clone());
+        let merged_dev_bind_try = if overlay.dev_bind_try.is_empty() {
+            base.dev_bind_try
+        } else {
+            apply_removals(deduplicate_vec(merged_dev_bind_try))
+        };
+
+        let mut merged_tmpfs = base.tmpfs.clone();
+        merged_tmpfs.extend(overlay.tmpfs.clone());
+        let merged_tmpfs = if overlay.tmpfs.is_empty() {
+            base.tmpfs
+        } else {
+            apply_removals(deduplicate_vec(merged_tmpfs))
+        };
+
+        let mut merged_unset_env = base.unset_env.clone();
+        merged_unset_env.extend(overlay.unset_env.clone());
+        let merged_unset_env = if overlay.unset_env.is_empty() {
+            base.unset_env
+        } else {
+            apply_removals(deduplicate_vec(merged_unset_env))
+        };
+
+        let mut merged_cap = base.cap.clone();
+        merged_cap.extend(overlay.cap.clone());
+        let merged_cap = if overlay.cap.is_empty() {
+            base.cap
+        } else {
+            apply_removals(deduplicate_vec(merged_cap))
+        };
+
+        let mut merged_cap_drop = base.cap_drop.clone();
+        merged_cap_drop.extend(overlay.cap_drop.clone());
+        let merged_cap_drop = if overlay.cap_drop.is_empty() {
+            base.cap_drop
+        } else {
+            apply_removals(deduplicate_vec(merged_cap_drop))
+        };
+
+        let mut merged_env = base.env.clone();
+        merged_env.extend(overlay.env);
+
+        Entry {
+            entry_type: base.entry_type,
+            enabled: overlay.enabled.unwrap_or(base.enabled),
+            override_parent: overlay.override_parent.unwrap_or(base.override_parent),
+            extends: base.extends,
+            share: merged_share,
+            bind: merged_bind,
+            ro_bind: merged_ro_bind,
+            dev_bind: merged_dev_bind,
+            bind_try: merged_bind_try,
+            ro_bind_try: merged_ro_bind_try,
+            dev_bind_try: merged_dev_bind_try,
+            tmpfs: merged_tmpfs,
+            chdir: overlay.chdir.or(base.chdir),
+            die_with_parent: overlay.die_with_parent.unwrap_or(base.die_with_parent),
+            new_session: overlay.new_session.unwrap_or(base.new_session),
+            cap: merged_cap,
+            cap_drop: merged_cap_drop,
             env: merged_env,
             unset_env: merged_unset_env,
+            seccomp: overlay.seccomp.or(base.seccomp),
+            resources: overlay.resources.or(base.resources),
+            profiles: base.profiles,
+            alias: base.alias,
+            args: if overlay.args.is_empty() { base.args } else { overlay.args },
         }
     }
 }
 
+/// Where a resolved field's value ultimately came from, for `sheld explain`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The user-level config file (`~/.config/shwrap/default.yaml`).
+    User,
+    /// The project-local config file (`.shwrap.yaml`).
+    Local,
+    /// A path named in the `SHWRAP_CONFIG` environment variable.
+    Env,
+    /// Inherited from a model of this name via `extends`.
+    Model(String),
+    /// The command entry's own value, when there's no layer to name.
+    CommandSelf,
+}
+
+/// A single resolved value, annotated with where it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotatedValue {
+    pub value: String,
+    pub source: Source,
+}
+
+/// A fully resolved `Entry`, with every array/env value annotated with its
+/// origin so `sheld explain` can answer "why is `network` in node's share?".
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedEntry {
+    pub share: Vec<AnnotatedValue>,
+    pub bind: Vec<AnnotatedValue>,
+    pub ro_bind: Vec<AnnotatedValue>,
+    pub dev_bind: Vec<AnnotatedValue>,
+    pub bind_try: Vec<AnnotatedValue>,
+    pub ro_bind_try: Vec<AnnotatedValue>,
+    pub dev_bind_try: Vec<AnnotatedValue>,
+    pub tmpfs: Vec<AnnotatedValue>,
+    pub cap: Vec<AnnotatedValue>,
+    pub unset_env: Vec<AnnotatedValue>,
+    pub env: HashMap<String, AnnotatedValue>,
+}
+
+impl ResolvedEntry {
+    /// Append another resolved entry's values onto this one (ancestors
+    /// first, so later `extend` calls take precedence on scalar lookups).
+    fn extend(&mut self, other: ResolvedEntry) {
+        self.share.extend(other.share);
+        self.bind.extend(other.bind);
+        self.ro_bind.extend(other.ro_bind);
+        self.dev_bind.extend(other.dev_bind);
+        self.bind_try.extend(other.bind_try);
+        self.ro_bind_try.extend(other.ro_bind_try);
+        self.dev_bind_try.extend(other.dev_bind_try);
+        self.tmpfs.extend(other.tmpfs);
+        self.cap.extend(other.cap);
+        self.unset_env.extend(other.unset_env);
+        self.env.extend(other.env);
+    }
+
+    /// Tag every value of a plain `Entry` with `source` and append it.
+    fn push_entry(&mut self, entry: &Entry, source: &Source) {
+        let tag = |values: &[String]| -> Vec<AnnotatedValue> {
+            values
+                .iter()
+                .map(|v| AnnotatedValue {
+                    value: v.clone(),
+                    source: source.clone(),
+                })
+                .collect()
+        };
+
+        self.share.extend(tag(&entry.share));
+        self.bind.extend(tag(&entry.bind));
+        self.ro_bind.extend(tag(&entry.ro_bind));
+        self.dev_bind.extend(tag(&entry.dev_bind));
+        self.bind_try.extend(tag(&entry.bind_try));
+        self.ro_bind_try.extend(tag(&entry.ro_bind_try));
+        self.dev_bind_try.extend(tag(&entry.dev_bind_try));
+        self.tmpfs.extend(tag(&entry.tmpfs));
+        self.cap.extend(tag(&entry.cap));
+        self.unset_env.extend(tag(&entry.unset_env));
+        for (k, v) in &entry.env {
+            self.env.insert(
+                k.clone(),
+                AnnotatedValue {
+                    value: v.clone(),
+                    source: source.clone(),
+                },
+            );
+        }
+    }
+
+    /// Deduplicate by value (first occurrence kept, like `deduplicate_vec`)
+    /// and apply `!`-prefixed removals (see `apply_removals`) across every
+    /// annotated array field, so explain output reflects the same final
+    /// values a real merge would produce.
+    fn apply_removals(&mut self) {
+        fn filtered(values: Vec<AnnotatedValue>) -> Vec<AnnotatedValue> {
+            let removals: HashSet<String> = values
+                .iter()
+                .filter_map(|av| av.value.strip_prefix('!').map(String::from))
+                .collect();
+
+            let mut seen = HashSet::new();
+            values
+                .into_iter()
+                .filter(|av| {
+                    let bare = av.value.strip_prefix('!').unwrap_or(&av.value);
+                    !removals.contains(bare) && seen.insert(av.value.clone())
+                })
+                .collect()
+        }
+
+        self.share = filtered(std::mem::take(&mut self.share));
+        self.bind = filtered(std::mem::take(&mut self.bind));
+        self.ro_bind = filtered(std::mem::take(&mut self.ro_bind));
+        self.dev_bind = filtered(std::mem::take(&mut self.dev_bind));
+        self.bind_try = filtered(std::mem::take(&mut self.bind_try));
+        self.ro_bind_try = filtered(std::mem::take(&mut self.ro_bind_try));
+        self.dev_bind_try = filtered(std::mem::take(&mut self.dev_bind_try));
+        self.tmpfs = filtered(std::mem::take(&mut self.tmpfs));
+        self.unset_env = filtered(std::mem::take(&mut self.unset_env));
+        self.cap = filtered(std::mem::take(&mut self.cap));
+    }
+}
+
 impl Config {
     pub fn from_yaml(yaml: &str) -> Result<Self> {
-        let config: Config = serde_yaml::from_str(yaml).context("Failed to parse YAML config")?;
+        let raw: serde_yaml::Value = serde_yaml::from_str(yaml).context("Failed to parse YAML config")?;
+        check_schema_version(&raw)?;
+        validate_entries(&raw)?;
+
+        let config: Config =
+            serde_yaml::from_value(raw).context("Failed to parse YAML config")?;
 
         Ok(config)
     }
@@ -264,7 +1198,14 @@ impl Config {
         let yaml = fs::read_to_string(path.as_ref())
             .context(format!("Failed to read config file: {:?}", path.as_ref()))?;
 
-        let config: Config = serde_yaml::from_str(&yaml)
+        let raw: serde_yaml::Value = serde_yaml::from_str(&yaml)
+            .context(format!("Failed to parse YAML config {:?}", path.as_ref()))?;
+        check_schema_version(&raw)
+            .with_context(|| format!("Invalid config file: {:?}", path.as_ref()))?;
+        validate_entries(&raw)
+            .with_context(|| format!("Invalid config file: {:?}", path.as_ref()))?;
+
+        let config: Config = serde_yaml::from_value(raw)
             .context(format!("Failed to parse YAML config {:?}", path.as_ref()))?;
 
         Ok(config)
@@ -317,22 +1258,138 @@ impl Config {
 
     /// Get a specific command configuration
     pub fn get_command(&self, name: &str) -> Option<Entry> {
-        self.entries
+        self.get_command_checked(name).ok()
+    }
+
+    /// Like `get_command`, but distinguishes *why* a name didn't resolve to
+    /// a usable command via `ConfigError`, instead of folding "missing" and
+    /// "wrong type" into the same `None`.
+    pub fn get_command_checked(&self, name: &str) -> Result<Entry, ConfigError> {
+        let entry = self
+            .entries
             .get(name)
-            .filter(|entry| entry.entry_type == EntryType::Command)
-            .map(|entry| entry.clone().into())
+            .ok_or_else(|| ConfigError::NotFound(name.to_string()))?;
+
+        if entry.entry_type != EntryType::Command {
+            return Err(ConfigError::WrongType {
+                name: name.to_string(),
+                expected: "command",
+                found: entry_type_label(&entry.entry_type),
+            });
+        }
+
+        Ok(entry.clone().into())
     }
 
-    /// Get all model entries (filtering by type: command)
-    pub fn get_models(&self) -> HashMap<String, Entry> {
+    /// Names of everything invokable from the command line: both regular
+    /// `type: command` entries and `type: alias` entries, since an alias is
+    /// meant to be typed on the command line just like the command it points to.
+    pub fn command_names(&self) -> Vec<String> {
         self.entries
             .iter()
-            .filter(|(_, entry)| entry.entry_type == EntryType::Model)
-            .map(|(name, entry)| (name.clone(), entry.clone().into()))
+            .filter(|(_, entry)| matches!(entry.entry_type, EntryType::Command | EntryType::Alias))
+            .map(|(name, _)| name.clone())
             .collect()
     }
 
-    /// Get a model entry by name
+    /// Follow a `type: alias` entry to the `type: command` entry it ultimately
+    /// points to, mirroring `resolve_ancestors`'s cycle detection: an
+    /// `in_progress` set catches the cycle, `stack` renders it for the error.
+    /// The alias's own `share`/`bind`/`env`/... override the target's via
+    /// `Entry::deep_merge` (alias wins, just like a child overrides a parent
+    /// in `extends`), and each alias's fixed `args` are accumulated, furthest
+    /// ancestor first, so they land ahead of whatever the invoker passes.
+    ///
+    /// Returns the name of the ultimate `type: command` entry (what should
+    /// actually be exec'd), its resolved `Entry` (still carrying whatever
+    /// `extends` it declared, for `merge_with_template` to flatten as
+    /// usual), and the accumulated fixed args. `Ok(None)` if `name` doesn't
+    /// exist at all; `Err(ConfigError::WrongType)` if it exists but is a
+    /// `type: model` entry, which can't be invoked directly.
+    pub fn resolve_command(&self, name: &str) -> Result<Option<(String, Entry, Vec<String>)>> {
+        let mut in_progress = HashSet::new();
+        let mut stack = Vec::new();
+        self.resolve_command_inner(name, &mut in_progress, &mut stack)
+    }
+
+    fn resolve_command_inner(
+        &self,
+        name: &str,
+        in_progress: &mut HashSet<String>,
+        stack: &mut Vec<String>,
+    ) -> Result<Option<(String, Entry, Vec<String>)>> {
+        let Some(entry) = self.entries.get(name) else {
+            return Ok(None);
+        };
+
+        match entry.entry_type {
+            EntryType::Command => Ok(Some((name.to_string(), entry.clone().into(), Vec::new()))),
+            EntryType::Alias => {
+                let Some(target_name) = &entry.alias else {
+                    bail!("Alias '{}' does not specify a target", name);
+                };
+
+                if in_progress.contains(name) {
+                    stack.push(name.to_string());
+                    return Err(ConfigError::Cycle {
+                        kind: "alias",
+                        path: stack.join(" -> "),
+                    }
+                    .into());
+                }
+                in_progress.insert(name.to_string());
+                stack.push(name.to_string());
+
+                let resolved = self.resolve_command_inner(target_name, in_progress, stack)?;
+
+                stack.pop();
+                in_progress.remove(name);
+
+                let Some((resolved_name, target, mut args)) = resolved else {
+                    bail!("Alias '{}' points to nonexistent command '{}'", name, target_name);
+                };
+
+                if !target.enabled {
+                    bail!("Alias '{}' points to disabled command '{}'", name, resolved_name);
+                }
+
+                let target_extends = target.extends.clone();
+                let mut merged = Entry::deep_merge(target, entry.clone().into());
+                // The alias entry itself is not a command; once resolved the
+                // result should behave exactly like the command it points to.
+                merged.entry_type = EntryType::Command;
+                merged.alias = None;
+                // An alias rarely declares its own `extends`, and
+                // `deep_merge` otherwise lets the (empty) child value win,
+                // which would silently drop the target's model chain.
+                if entry.extends.is_empty() {
+                    merged.extends = target_extends;
+                }
+                args.extend(entry.args.clone());
+                Ok(Some((resolved_name, merged, args)))
+            }
+            // Unlike a missing name (`Ok(None)` above), this name does exist
+            // but isn't invokable; report why instead of folding it into
+            // the same "not found" outcome.
+            EntryType::Model => Err(ConfigError::WrongType {
+                name: name.to_string(),
+                expected: "command",
+                found: "model",
+            }
+            .into()),
+        }
+    }
+
+    /// Get all model entries (filtering by type: command)
+    pub fn get_models(&self) -> HashMap<String, Entry> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| entry.entry_type == EntryType::Model)
+            .map(|(name, entry)| (name.clone(), entry.clone().into()))
+            .collect()
+    }
+
+    /// Get a model entry by name
     fn get_model(&self, name: &str) -> Option<Entry> {
         self.entries
             .get(name)
@@ -340,85 +1397,281 @@ impl Config {
             .map(|entry| entry.clone().into())
     }
 
-    /// Merge command config with its templates (if extends is set)
-    /// Models are applied in order, with later models overriding earlier ones
-    pub fn merge_with_template(&self, cmd_config: Entry) -> Entry {
-        // Save the command's original values to apply at the end
-        let cmd_share = cmd_config.share.clone();
-        let cmd_bind = cmd_config.bind.clone();
-        let cmd_ro_bind = cmd_config.ro_bind.clone();
-        let cmd_dev_bind = cmd_config.dev_bind.clone();
-        let cmd_bind_try = cmd_config.bind_try.clone();
-        let cmd_ro_bind_try = cmd_config.ro_bind_try.clone();
-        let cmd_dev_bind_try = cmd_config.dev_bind_try.clone();
-        let cmd_tmpfs = cmd_config.tmpfs.clone();
-        let cmd_unset_env = cmd_config.unset_env.clone();
-        let cmd_env = cmd_config.env.clone();
-        let cmd_cap = cmd_config.cap.clone();
+    /// Recursively flatten the ancestor models named in `extends`, applying
+    /// deeper ancestors first so nearer/later models override them. `stack`
+    /// tracks the in-progress chain for error reporting; `in_progress` is
+    /// the same chain as a set, for O(1) cycle checks. `visited` memoizes
+    /// each model's own fully-flattened ancestor chain by name, so a
+    /// diamond graph (e.g. two models both extending a common base) only
+    /// walks the shared base once. Nonexistent models are skipped, matching
+    /// the previous (non-transitive) behavior.
+    fn resolve_ancestors(
+        &self,
+        extends: &[String],
+        in_progress: &mut HashSet<String>,
+        visited: &mut HashMap<String, Entry>,
+        stack: &mut Vec<String>,
+    ) -> Result<Entry> {
+        let mut acc = blank_model_entry();
+
+        for model_name in extends {
+            let Some(template) = self.get_model(model_name) else {
+                continue; // Nonexistent model: skip it (no error)
+            };
+
+            if in_progress.contains(model_name) {
+                stack.push(model_name.clone());
+                return Err(ConfigError::Cycle {
+                    kind: "extends",
+                    path: stack.join(" -> "),
+                }
+                .into());
+            }
+
+            let flattened = if let Some(cached) = visited.get(model_name) {
+                cached.clone()
+            } else {
+                in_progress.insert(model_name.clone());
+                stack.push(model_name.clone());
+
+                let ancestors = self.resolve_ancestors(&template.extends, in_progress, visited, stack)?;
+
+                stack.pop();
+                in_progress.remove(model_name);
+
+                let mut flattened = ancestors;
+                flattened.share.extend(template.share.clone());
+                flattened.bind.extend(template.bind.clone());
+                flattened.ro_bind.extend(template.ro_bind.clone());
+                flattened.dev_bind.extend(template.dev_bind.clone());
+                flattened.bind_try.extend(template.bind_try.clone());
+                flattened.ro_bind_try.extend(template.ro_bind_try.clone());
+                flattened.dev_bind_try.extend(template.dev_bind_try.clone());
+                flattened.tmpfs.extend(template.tmpfs.clone());
+                flattened.unset_env.extend(template.unset_env.clone());
+                flattened.cap.extend(template.cap.clone());
+                flattened.cap_drop.extend(template.cap_drop.clone());
+                flattened.env.extend(template.env.clone());
+
+                visited.insert(model_name.clone(), flattened.clone());
+                flattened
+            };
+
+            // Ancestors of this model first, then the model's own values.
+            acc.share.extend(flattened.share);
+            acc.bind.extend(flattened.bind);
+            acc.ro_bind.extend(flattened.ro_bind);
+            acc.dev_bind.extend(flattened.dev_bind);
+            acc.bind_try.extend(flattened.bind_try);
+            acc.ro_bind_try.extend(flattened.ro_bind_try);
+            acc.dev_bind_try.extend(flattened.dev_bind_try);
+            acc.tmpfs.extend(flattened.tmpfs);
+            acc.unset_env.extend(flattened.unset_env);
+            acc.cap.extend(flattened.cap);
+            acc.cap_drop.extend(flattened.cap_drop);
+            acc.env.extend(flattened.env);
+        }
+
+        Ok(acc)
+    }
+
+    /// Merge command config with its templates (if extends is set).
+    /// `extends` is resolved transitively: a model that itself extends
+    /// other models has those ancestors flattened in first, deepest first,
+    /// so nearer models and the command's own values take precedence.
+    /// A cycle anywhere in the chain is reported as an error naming the
+    /// full cycle path (e.g. `strict -> base -> strict`).
+    pub fn merge_with_template(&self, cmd_config: Entry) -> Result<Entry> {
+        let mut in_progress = HashSet::new();
+        let mut visited = HashMap::new();
+        let mut stack = Vec::new();
+        let ancestors = self.resolve_ancestors(&cmd_config.extends, &mut in_progress, &mut visited, &mut stack)?;
 
         let mut result = Entry {
             entry_type: cmd_config.entry_type.clone(),
             enabled: cmd_config.enabled,
             override_parent: cmd_config.override_parent,
             extends: vec![], // Clear extends after processing
-            share: vec![],
-            bind: vec![],
-            ro_bind: vec![],
-            dev_bind: vec![],
-            bind_try: vec![],
-            ro_bind_try: vec![],
-            dev_bind_try: vec![],
-            tmpfs: vec![],
+            share: ancestors.share,
+            bind: ancestors.bind,
+            ro_bind: ancestors.ro_bind,
+            dev_bind: ancestors.dev_bind,
+            bind_try: ancestors.bind_try,
+            ro_bind_try: ancestors.ro_bind_try,
+            dev_bind_try: ancestors.dev_bind_try,
+            tmpfs: ancestors.tmpfs,
             chdir: cmd_config.chdir.clone(),
             die_with_parent: cmd_config.die_with_parent,
             new_session: cmd_config.new_session,
-            cap: vec![],
-            env: HashMap::new(),
-            unset_env: vec![],
+            cap: ancestors.cap,
+            cap_drop: ancestors.cap_drop,
+            env: ancestors.env,
+            unset_env: ancestors.unset_env,
+            seccomp: cmd_config.seccomp.clone(),
+            resources: cmd_config.resources.clone(),
+            profiles: cmd_config.profiles.clone(),
+            alias: cmd_config.alias.clone(),
+            args: cmd_config.args.clone(),
         };
 
-        // Iterate over each model in the extends list
-        for model_name in &cmd_config.extends {
-            if let Some(template) = self.get_model(model_name) {
-                // Extend arrays with template values
-                result.share.extend(template.share.clone());
-                result.bind.extend(template.bind.clone());
-                result.ro_bind.extend(template.ro_bind.clone());
-                result.dev_bind.extend(template.dev_bind.clone());
-                result.bind_try.extend(template.bind_try.clone());
-                result.ro_bind_try.extend(template.ro_bind_try.clone());
-                result.dev_bind_try.extend(template.dev_bind_try.clone());
-                result.tmpfs.extend(template.tmpfs.clone());
-                result.unset_env.extend(template.unset_env.clone());
-                result.cap.extend(template.cap.clone());
-
-                // Merge env (later templates override earlier ones)
-                result.env.extend(template.env.clone());
-            }
-            // If model doesn't exist, skip it (no error)
-        }
-
         // Finally, apply command's own values (command values take precedence)
-        result.share.extend(cmd_share);
-        result.bind.extend(cmd_bind);
-        result.ro_bind.extend(cmd_ro_bind);
-        result.dev_bind.extend(cmd_dev_bind);
-        result.bind_try.extend(cmd_bind_try);
-        result.ro_bind_try.extend(cmd_ro_bind_try);
-        result.dev_bind_try.extend(cmd_dev_bind_try);
-        result.tmpfs.extend(cmd_tmpfs);
-        result.unset_env.extend(cmd_unset_env);
-        result.cap.extend(cmd_cap);
-        result.env.extend(cmd_env);
-
-        result
+        result.share.extend(cmd_config.share);
+        result.bind.extend(cmd_config.bind);
+        result.ro_bind.extend(cmd_config.ro_bind);
+        result.dev_bind.extend(cmd_config.dev_bind);
+        result.bind_try.extend(cmd_config.bind_try);
+        result.ro_bind_try.extend(cmd_config.ro_bind_try);
+        result.dev_bind_try.extend(cmd_config.dev_bind_try);
+        result.tmpfs.extend(cmd_config.tmpfs);
+        result.unset_env.extend(cmd_config.unset_env);
+        result.cap.extend(cmd_config.cap);
+        result.cap_drop.extend(cmd_config.cap_drop);
+        result.env.extend(cmd_config.env);
+
+        // Dedup (a diamond `extends` graph can otherwise pull the same
+        // ancestor value in more than once) and apply `!`-prefixed
+        // removals so a command can drop something an extended model
+        // introduced without forking the whole model.
+        result.share = apply_removals(deduplicate_vec(result.share));
+        result.bind = apply_removals(deduplicate_vec(result.bind));
+        result.ro_bind = apply_removals(deduplicate_vec(result.ro_bind));
+        result.dev_bind = apply_removals(deduplicate_vec(result.dev_bind));
+        result.bind_try = apply_removals(deduplicate_vec(result.bind_try));
+        result.ro_bind_try = apply_removals(deduplicate_vec(result.ro_bind_try));
+        result.dev_bind_try = apply_removals(deduplicate_vec(result.dev_bind_try));
+        result.tmpfs = apply_removals(deduplicate_vec(result.tmpfs));
+        result.unset_env = apply_removals(deduplicate_vec(result.unset_env));
+        result.cap = apply_removals(deduplicate_vec(result.cap));
+        result.cap_drop = apply_removals(deduplicate_vec(result.cap_drop));
+
+        Ok(result)
     }
 
     // Deprecated: use merge_with_template instead
-    pub fn merge_with_base(&self, cmd_config: Entry) -> Entry {
+    pub fn merge_with_base(&self, cmd_config: Entry) -> Result<Entry> {
         self.merge_with_template(cmd_config)
     }
 
+    /// Get a command's configuration with a named profile applied on top.
+    /// The base entry is resolved via `merge_with_template` first, then the
+    /// named profile (if it exists) is applied over it via
+    /// `Entry::apply_profile`, which only overrides what the profile
+    /// actually set. Selecting a profile that doesn't exist on the entry
+    /// returns the base entry unchanged.
+    pub fn get_command_with_profile(&self, name: &str, profile: &str) -> Option<Result<Entry>> {
+        let cmd_config = self.get_command(name)?;
+        let selected_profile = cmd_config.profiles.get(profile).cloned();
+
+        Some(self.merge_with_template(cmd_config).map(|base| match selected_profile {
+            Some(overlay) => Entry::apply_profile(base, overlay),
+            None => base,
+        }))
+    }
+
+    /// Convenience for explaining an entry within a single already-resolved
+    /// config, with no separate user/local files to distinguish (e.g. a
+    /// `--config` override): the command's own fields are tagged
+    /// `Source::CommandSelf` rather than a named layer.
+    pub fn explain_entry_single(&self, name: &str) -> Result<Option<ResolvedEntry>> {
+        Self::explain_entry(&[(Source::CommandSelf, self.clone())], name)
+    }
+
+    /// Resolve `name` the same way `merge_with_template` does, but annotate
+    /// every array/env value with where it came from, so `sheld explain`
+    /// can answer "why is `network` in node's share?". `layers` is the
+    /// ordered (lowest to highest precedence) set of config files the
+    /// entry may be directly defined in, e.g. from `ConfigLoader::load_layers`.
+    /// Models named in `extends` are resolved transitively against the
+    /// merge of all layers, same as a normal load would see them.
+    pub fn explain_entry(layers: &[(Source, Config)], name: &str) -> Result<Option<ResolvedEntry>> {
+        let occurrences: Vec<(Source, Entry)> = layers
+            .iter()
+            .filter_map(|(source, config)| config.get_command(name).map(|entry| (source.clone(), entry)))
+            .collect();
+
+        let Some((_, last)) = occurrences.last() else {
+            return Ok(None);
+        };
+        // `extends` is a scalar-like field: the last layer to set it wins
+        // outright, matching `Entry::deep_merge`.
+        let extends = last.extends.clone();
+
+        let merged = layers
+            .iter()
+            .map(|(_, c)| c.clone())
+            .reduce(Config::merge)
+            .expect("layers is non-empty, since occurrences is non-empty");
+
+        let mut in_progress = HashSet::new();
+        let mut visited = HashMap::new();
+        let mut stack = Vec::new();
+        let mut result = merged.resolve_ancestors_annotated(&extends, &mut in_progress, &mut visited, &mut stack)?;
+
+        // Command's own values, tagged by whichever layer(s) set them.
+        for (source, entry) in &occurrences {
+            result.push_entry(entry, source);
+        }
+
+        result.apply_removals();
+
+        Ok(Some(result))
+    }
+
+    /// Recursively flatten `extends` into a `ResolvedEntry`, annotating each
+    /// value with the model that directly contributed it. Mirrors
+    /// `resolve_ancestors`, but carries provenance instead of plain values.
+    /// `visited` memoizes each model's own fully-flattened, annotated
+    /// ancestor chain by name, for the same reason `resolve_ancestors` does:
+    /// a diamond graph (e.g. two models both extending a common base) should
+    /// only walk, and report, the shared base once, instead of duplicating
+    /// its values in `sheld explain` output.
+    fn resolve_ancestors_annotated(
+        &self,
+        extends: &[String],
+        in_progress: &mut HashSet<String>,
+        visited: &mut HashMap<String, ResolvedEntry>,
+        stack: &mut Vec<String>,
+    ) -> Result<ResolvedEntry> {
+        let mut acc = ResolvedEntry::default();
+
+        for model_name in extends {
+            let Some(template) = self.get_model(model_name) else {
+                continue; // Nonexistent model: skip it (no error)
+            };
+
+            if in_progress.contains(model_name) {
+                stack.push(model_name.clone());
+                return Err(ConfigError::Cycle {
+                    kind: "extends",
+                    path: stack.join(" -> "),
+                }
+                .into());
+            }
+
+            let flattened = if let Some(cached) = visited.get(model_name) {
+                cached.clone()
+            } else {
+                in_progress.insert(model_name.clone());
+                stack.push(model_name.clone());
+
+                let ancestors = self.resolve_ancestors_annotated(&template.extends, in_progress, visited, stack)?;
+
+                stack.pop();
+                in_progress.remove(model_name);
+
+                let mut flattened = ancestors;
+                flattened.push_entry(&template, &Source::Model(model_name.clone()));
+
+                visited.insert(model_name.clone(), flattened.clone());
+                flattened
+            };
+
+            acc.extend(flattened);
+        }
+
+        Ok(acc)
+    }
+
     /// Merge another config into this one
     /// - Entries with the same name: depends on override field
     ///   - override: true -> child completely replaces parent
@@ -452,9 +1705,188 @@ impl Config {
         }
 
         Config {
+            version: child.version.or(parent.version),
+            policy: merge_policy(parent.policy, child.policy),
             entries: merged_entries,
         }
     }
+
+    /// Check a resolved entry (i.e. post `merge_with_template`) against this
+    /// config's `policy:` section, if any. Only `deny` rules can produce a
+    /// violation: `share` is matched exactly, `bind`/`ro_bind` are matched
+    /// by prefix against the host-side path. An explicit deny always wins,
+    /// so `allow` rules never override a matching `deny` — they only
+    /// document an exception for when no deny rule matches it anyway.
+    pub fn check_policy(&self, entry: &Entry) -> Result<(), PolicyViolation> {
+        let Some(policy) = &self.policy else {
+            return Ok(());
+        };
+
+        for value in &entry.share {
+            if let Some(rule_number) =
+                first_deny_match(&policy.rules, |rule| rule.share.as_deref() == Some(value.as_str()))
+            {
+                return Err(PolicyViolation { field: PolicyField::Share, value: value.clone(), rule_number });
+            }
+        }
+
+        for (field, values) in [(PolicyField::Bind, &entry.bind), (PolicyField::RoBind, &entry.ro_bind)] {
+            for raw in values {
+                let path = raw.split(':').next().unwrap_or(raw);
+                if let Some(rule_number) = first_deny_match(&policy.rules, |rule| {
+                    rule.bind.as_deref().is_some_and(|prefix| path.starts_with(prefix))
+                }) {
+                    return Err(PolicyViolation { field: field.clone(), value: path.to_string(), rule_number });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Enforce this config's policy on a resolved entry, applying the
+    /// configured `on_violation` action (`reject` by default). `reject`
+    /// turns the first violation into an error; `strip` instead removes
+    /// every `share`/`bind`/`ro_bind` value a deny rule matches, looping
+    /// until the entry passes (dropping a value can't introduce a new one).
+    pub fn enforce_policy(&self, mut entry: Entry) -> Result<Entry> {
+        let Some(policy) = &self.policy else {
+            return Ok(entry);
+        };
+        let action = policy.on_violation.unwrap_or_default();
+
+        loop {
+            let violation = match self.check_policy(&entry) {
+                Ok(()) => return Ok(entry),
+                Err(violation) => violation,
+            };
+
+            if action == PolicyAction::Reject {
+                bail!("{}", violation);
+            }
+
+            match violation.field {
+                PolicyField::Share => entry.share.retain(|v| v != &violation.value),
+                PolicyField::Bind => {
+                    entry.bind.retain(|raw| raw.split(':').next().unwrap_or(raw) != violation.value)
+                }
+                PolicyField::RoBind => {
+                    entry.ro_bind.retain(|raw| raw.split(':').next().unwrap_or(raw) != violation.value)
+                }
+            }
+        }
+    }
+
+    /// Fold an ordered stack of named layers into one config, lowest
+    /// priority first. Each step uses the same `merge` semantics as a plain
+    /// two-way merge (per-entry `override`/`enabled`/array-dedup), so this
+    /// is a drop-in generalization of merging a fixed user+local pair to
+    /// an arbitrary number of layers (e.g. a system-wide default, a user
+    /// config, a project config, and an env-driven override). An empty
+    /// list of layers, or layers whose configs have no entries, are a
+    /// no-op; returns `None` only if `layers` is empty.
+    pub fn merge_layers(layers: Vec<(LayerKind, Config)>) -> Option<Config> {
+        layers.into_iter().map(|(_, config)| config).reduce(Config::merge)
+    }
+
+    /// Find commands defined in both `user` and `local` whose `share`,
+    /// `bind`, or `enabled` disagree between the two layers, rather than
+    /// letting `Config::merge`'s "local wins" rule resolve the shadowing
+    /// silently. Used by `sheld validate` to surface (and, under
+    /// `--strict`, reject) accidental overrides across the config
+    /// hierarchy, borrowing the idea from jj's `AmbiguousSource` check:
+    /// conflicting config should be loud, not quietly collapsed.
+    ///
+    /// Only `type: command` entries are considered: `type: model` templates
+    /// aren't commands, and reporting one under `ConflictingOverride`'s
+    /// `command` field would mislabel it as one in `sheld validate`'s output.
+    pub fn conflicting_overrides(user: &Config, local: &Config) -> Vec<ConflictingOverride> {
+        let mut conflicts = Vec::new();
+
+        let mut names: Vec<&String> = user
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.entry_type == EntryType::Command)
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+
+        for name in names {
+            let user_entry = &user.entries[name];
+            let Some(local_entry) = local.entries.get(name) else {
+                continue;
+            };
+            if local_entry.entry_type != EntryType::Command {
+                continue;
+            }
+
+            let mut user_share = user_entry.share.clone();
+            user_share.sort();
+            let mut local_share = local_entry.share.clone();
+            local_share.sort();
+            if user_share != local_share {
+                conflicts.push(ConflictingOverride {
+                    command: name.clone(),
+                    field: "share",
+                    user_value: user_share.join(", "),
+                    local_value: local_share.join(", "),
+                });
+            }
+
+            let mut user_bind = user_entry.bind.clone();
+            user_bind.sort();
+            let mut local_bind = local_entry.bind.clone();
+            local_bind.sort();
+            if user_bind != local_bind {
+                conflicts.push(ConflictingOverride {
+                    command: name.clone(),
+                    field: "bind",
+                    user_value: user_bind.join(", "),
+                    local_value: local_bind.join(", "),
+                });
+            }
+
+            if user_entry.enabled != local_entry.enabled {
+                conflicts.push(ConflictingOverride {
+                    command: name.clone(),
+                    field: "enabled",
+                    user_value: user_entry.enabled.to_string(),
+                    local_value: local_entry.enabled.to_string(),
+                });
+            }
+        }
+
+        conflicts
+    }
+}
+
+/// One field of one command where the user and local configs disagree,
+/// reported by `Config::conflicting_overrides`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictingOverride {
+    pub command: String,
+    pub field: &'static str,
+    pub user_value: String,
+    pub local_value: String,
+}
+
+/// A named layer in the config priority stack, lowest to highest priority.
+/// Mirrors `loader::ConfigLayer`, but names the layer's role in the stack
+/// rather than where it was discovered on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerKind {
+    /// Built-in defaults, lower priority than anything on disk.
+    Default,
+    /// System-wide config (e.g. `/etc/shwrap`).
+    Global,
+    /// Per-user config (e.g. `~/.config/shwrap`).
+    User,
+    /// Project-local config (e.g. `.shwrap.yaml`), walked up from cwd.
+    LocalRepo,
+    /// Config supplied via an environment variable.
+    Env,
+    /// Config supplied directly on the command line (e.g. `--config`).
+    CommandArg,
 }
 
 #[cfg(test)]
@@ -486,6 +1918,55 @@ mod tests {
         assert_eq!(node_cmd.bind, vec!["~/.npm:~/.npm"]);
     }
 
+    #[test]
+    fn test_unknown_field_rejected() {
+        let err = Config::from_yaml(indoc! {"
+            node:
+              enabled: true
+              shre:
+                - user
+        "})
+        .unwrap_err();
+        assert!(err.to_string().contains("shre"));
+        assert!(err.to_string().contains("node"));
+    }
+
+    #[test]
+    fn test_unknown_profile_field_rejected() {
+        let err = Config::from_yaml(indoc! {"
+            node:
+              profiles:
+                offline:
+                  ro_binds:
+                    - /usr
+        "})
+        .unwrap_err();
+        assert!(err.to_string().contains("ro_binds"));
+    }
+
+    #[test]
+    fn test_future_schema_version_rejected() {
+        let err = Config::from_yaml(indoc! {"
+            version: 999
+            node:
+              enabled: true
+        "})
+        .unwrap_err();
+        assert!(err.to_string().contains("999"));
+    }
+
+    #[test]
+    fn test_current_schema_version_accepted() {
+        let config = Config::from_yaml(indoc! {"
+            version: 1
+            node:
+              enabled: true
+        "})
+        .unwrap();
+        assert_eq!(config.version, Some(1));
+        assert!(config.get_command("node").is_some());
+    }
+
     #[test]
     fn test_parse_config_with_base() {
         let config = Config::from_yaml(indoc! {"
@@ -524,6 +2005,150 @@ mod tests {
         assert!(config.get_command("ruby").is_none());
     }
 
+    #[test]
+    fn test_get_command_with_profile() {
+        let config = Config::from_yaml(indoc! {"
+            base:
+              type: model
+              share:
+                - user
+
+            node:
+              extends: base
+              bind:
+                - ~/.npm:~/.npm
+              profiles:
+                offline:
+                  share:
+                    - \"!network\"
+                ci:
+                  env:
+                    CI: \"true\"
+        "})
+        .unwrap();
+
+        // Nonexistent profile: base entry unchanged.
+        let base = config.get_command_with_profile("node", "nope").unwrap().unwrap();
+        assert_eq!(base.share, vec!["user"]);
+        assert_eq!(base.bind, vec!["~/.npm:~/.npm"]);
+
+        // `ci` profile: env merged in on top of the base.
+        let ci = config.get_command_with_profile("node", "ci").unwrap().unwrap();
+        assert_eq!(ci.env.get("CI"), Some(&"true".to_string()));
+        assert_eq!(ci.bind, vec!["~/.npm:~/.npm"]);
+
+        // Unknown command: None.
+        assert!(config.get_command_with_profile("ruby", "ci").is_none());
+    }
+
+    #[test]
+    fn test_get_command_with_profile_preserves_unset_scalars() {
+        // `ci` only restates `env`, so selecting it must not reset
+        // `die_with_parent`/`new_session`/`enabled` to their struct
+        // defaults: a profile is a partial overlay, not a full entry.
+        let config = Config::from_yaml(indoc! {"
+            node:
+              enabled: false
+              die_with_parent: true
+              new_session: true
+              profiles:
+                ci:
+                  env:
+                    CI: \"true\"
+        "})
+        .unwrap();
+
+        let ci = config.get_command_with_profile("node", "ci").unwrap().unwrap();
+        assert!(!ci.enabled);
+        assert!(ci.die_with_parent);
+        assert!(ci.new_session);
+        assert_eq!(ci.env.get("CI"), Some(&"true".to_string()));
+    }
+
+    #[test]
+    fn test_explain_entry_single_source() {
+        let config = Config::from_yaml(indoc! {"
+            base:
+              type: model
+              share:
+                - user
+
+            node:
+              extends: base
+              bind:
+                - ~/.npm:~/.npm
+        "})
+        .unwrap();
+
+        let resolved = config.explain_entry_single("node").unwrap().unwrap();
+        assert_eq!(resolved.share.len(), 1);
+        assert_eq!(resolved.share[0].value, "user");
+        assert_eq!(resolved.share[0].source, Source::Model("base".to_string()));
+
+        assert_eq!(resolved.bind.len(), 1);
+        assert_eq!(resolved.bind[0].value, "~/.npm:~/.npm");
+        assert_eq!(resolved.bind[0].source, Source::CommandSelf);
+
+        assert!(config.explain_entry_single("ruby").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_explain_entry_dedupes_diamond_extends() {
+        // `node` extends both `a` and `b`, which both extend `base`. A real
+        // merge (`resolve_ancestors`, via `merge_with_template`) only pulls
+        // `base`'s shared `share` value in once; `explain` should report the
+        // same thing instead of duplicating it per extending branch.
+        let config = Config::from_yaml(indoc! {"
+            base:
+              type: model
+              share:
+                - user
+
+            a:
+              type: model
+              extends: base
+
+            b:
+              type: model
+              extends: base
+
+            node:
+              extends:
+                - a
+                - b
+        "})
+        .unwrap();
+
+        let resolved = config.explain_entry_single("node").unwrap().unwrap();
+        assert_eq!(resolved.share.len(), 1);
+        assert_eq!(resolved.share[0].value, "user");
+        assert_eq!(resolved.share[0].source, Source::Model("base".to_string()));
+    }
+
+    #[test]
+    fn test_explain_entry_across_layers() {
+        let user = Config::from_yaml(indoc! {"
+            node:
+              share:
+                - user
+        "})
+        .unwrap();
+        let local = Config::from_yaml(indoc! {"
+            node:
+              bind:
+                - ~/.npm:~/.npm
+        "})
+        .unwrap();
+
+        let layers = vec![(Source::User, user), (Source::Local, local)];
+        let resolved = Config::explain_entry(&layers, "node").unwrap().unwrap();
+
+        assert_eq!(resolved.share.len(), 1);
+        assert_eq!(resolved.share[0].source, Source::User);
+        assert_eq!(resolved.bind.len(), 1);
+        assert_eq!(resolved.bind[0].source, Source::Local);
+    }
+
     #[test]
     fn test_merge_with_base() {
         let config = Config::from_yaml(indoc! {"
@@ -541,7 +2166,7 @@ mod tests {
         "})
         .unwrap();
         let node_cmd = config.get_command("node").unwrap();
-        let merged = config.merge_with_base(node_cmd);
+        let merged = config.merge_with_base(node_cmd).unwrap();
 
         // Should have both base and command-specific settings
         assert_eq!(merged.share, vec!["user"]);
@@ -563,7 +2188,7 @@ mod tests {
         "})
         .unwrap();
         let node_cmd = config.get_command("node").unwrap();
-        let merged = config.merge_with_base(node_cmd.clone());
+        let merged = config.merge_with_base(node_cmd.clone()).unwrap();
 
         // Should not merge base since extends is not set
         assert_eq!(merged.share, node_cmd.share);
@@ -691,14 +2316,14 @@ mod tests {
         // Test node with minimal template
         let node_cmd = config.get_command("node").unwrap();
         assert_eq!(node_cmd.extends, vec!["minimal"]);
-        let merged_node = config.merge_with_template(node_cmd);
+        let merged_node = config.merge_with_template(node_cmd).unwrap();
         assert_eq!(merged_node.share, vec!["user", "network"]);
         assert_eq!(merged_node.bind, vec!["~/.npm:~/.npm"]);
 
         // Test python with strict template
         let python_cmd = config.get_command("python").unwrap();
         assert_eq!(python_cmd.extends, vec!["strict"]);
-        let merged_python = config.merge_with_template(python_cmd);
+        let merged_python = config.merge_with_template(python_cmd).unwrap();
         assert_eq!(merged_python.share, vec!["user"]);
         assert_eq!(merged_python.ro_bind, vec!["/usr"]);
         assert_eq!(merged_python.bind, vec!["~/.local:~/.local"]);
@@ -719,7 +2344,7 @@ mod tests {
         "})
         .unwrap();
         let node_cmd = config.get_command("node").unwrap();
-        let merged = config.merge_with_template(node_cmd.clone());
+        let merged = config.merge_with_template(node_cmd.clone()).unwrap();
 
         // Should not merge anything, just return the original command config
         assert_eq!(merged.share, node_cmd.share);
@@ -727,46 +2352,138 @@ mod tests {
     }
 
     #[test]
-    fn test_get_entries_with() {
+    fn test_transitive_extends() {
         let config = Config::from_yaml(indoc! {"
             base:
               type: model
               share:
                 - user
+              ro_bind:
+                - /usr
 
-            node:
-              enabled: true
-              extends: base
-              bind:
-                - ~/.npm:~/.npm
-
-            python:
-              enabled: false
-              extends: base
-              bind:
-                - ~/.local:~/.local
-
-            rust:
-              enabled: true
+            strict:
+              type: model
               extends: base
               share:
                 - network
+
+            node:
+              extends: strict
+              bind:
+                - ~/.npm:~/.npm
         "})
         .unwrap();
 
-        // Filter enabled commands only
-        let enabled = config.get_entries_with(|e| e.enabled && e.entry_type == EntryType::Command);
-        assert_eq!(enabled.len(), 2);
-        assert!(enabled.contains_key("node"));
-        assert!(enabled.contains_key("rust"));
-        assert!(!enabled.contains_key("python"));
-        assert!(!enabled.contains_key("base"));
+        let node_cmd = config.get_command("node").unwrap();
+        let merged = config.merge_with_template(node_cmd).unwrap();
 
-        // Filter disabled commands
-        let disabled =
-            config.get_entries_with(|e| !e.enabled && e.entry_type == EntryType::Command);
-        assert_eq!(disabled.len(), 1);
-        assert!(disabled.contains_key("python"));
+        // Ancestors applied deepest-first: base, then strict, then node's own.
+        assert_eq!(merged.share, vec!["user", "network"]);
+        assert_eq!(merged.ro_bind, vec!["/usr"]);
+        assert_eq!(merged.bind, vec!["~/.npm:~/.npm"]);
+    }
+
+    #[test]
+    fn test_extends_cycle_is_rejected() {
+        let config = Config::from_yaml(indoc! {"
+            strict:
+              type: model
+              extends: base
+
+            base:
+              type: model
+              extends: strict
+
+            node:
+              extends: strict
+              bind:
+                - ~/.npm:~/.npm
+        "})
+        .unwrap();
+
+        let node_cmd = config.get_command("node").unwrap();
+        let err = config.merge_with_template(node_cmd).unwrap_err();
+        assert!(err.to_string().contains("Cycle detected"));
+    }
+
+    #[test]
+    fn test_extends_diamond_dedups_shared_ancestor() {
+        // node extends both dev and ci, which both extend base: base's
+        // values should appear only once in the flattened result.
+        let config = Config::from_yaml(indoc! {"
+            base:
+              type: model
+              share:
+                - user
+
+            dev:
+              type: model
+              extends: base
+              bind:
+                - ~/.npm:~/.npm
+
+            ci:
+              type: model
+              extends: base
+              env:
+                CI: \"true\"
+
+            node:
+              extends:
+                - dev
+                - ci
+        "})
+        .unwrap();
+
+        let node_cmd = config.get_command("node").unwrap();
+        let merged = config.merge_with_template(node_cmd).unwrap();
+
+        assert_eq!(merged.share, vec!["user"]);
+        assert_eq!(merged.bind, vec!["~/.npm:~/.npm"]);
+        assert_eq!(merged.env.get("CI"), Some(&"true".to_string()));
+    }
+
+    #[test]
+    fn test_get_entries_with() {
+        let config = Config::from_yaml(indoc! {"
+            base:
+              type: model
+              share:
+                - user
+
+            node:
+              enabled: true
+              extends: base
+              bind:
+                - ~/.npm:~/.npm
+
+            python:
+              enabled: false
+              extends: base
+              bind:
+                - ~/.local:~/.local
+
+            rust:
+              enabled: true
+              extends: base
+              share:
+                - network
+        "})
+        .unwrap();
+
+        // Filter enabled commands only
+        let enabled = config.get_entries_with(|e| e.enabled && e.entry_type == EntryType::Command);
+        assert_eq!(enabled.len(), 2);
+        assert!(enabled.contains_key("node"));
+        assert!(enabled.contains_key("rust"));
+        assert!(!enabled.contains_key("python"));
+        assert!(!enabled.contains_key("base"));
+
+        // Filter disabled commands
+        let disabled =
+            config.get_entries_with(|e| !e.enabled && e.entry_type == EntryType::Command);
+        assert_eq!(disabled.len(), 1);
+        assert!(disabled.contains_key("python"));
 
         // Filter models
         let models = config.get_entries_with(|e| e.entry_type == EntryType::Model);
@@ -893,6 +2610,48 @@ mod tests {
         assert_eq!(all_enabled.len(), 3);
     }
 
+    #[test]
+    fn test_merge_layers_folds_lowest_to_highest() {
+        let global = Config::from_yaml(indoc! {"
+            node:
+              share:
+                - user
+        "})
+        .unwrap();
+
+        let user = Config::from_yaml(indoc! {"
+            node:
+              bind:
+                - ~/.npm:~/.npm
+        "})
+        .unwrap();
+
+        let local = Config::from_yaml(indoc! {"
+            node:
+              override: true
+              share:
+                - network
+        "})
+        .unwrap();
+
+        let merged = Config::merge_layers(vec![
+            (LayerKind::Global, global),
+            (LayerKind::User, user),
+            (LayerKind::LocalRepo, local),
+        ])
+        .unwrap();
+
+        let node_cmd = merged.get_command("node").unwrap();
+        // LocalRepo's `override: true` replaces everything below it.
+        assert_eq!(node_cmd.share, vec!["network"]);
+        assert!(node_cmd.bind.is_empty());
+    }
+
+    #[test]
+    fn test_merge_layers_empty_is_none() {
+        assert!(Config::merge_layers(vec![]).is_none());
+    }
+
     #[test]
     fn test_merge_both_configs_with_distinct_entries() {
         let user_config = Config::from_yaml(indoc! {"
@@ -967,7 +2726,7 @@ mod tests {
 
         let merged = Config::merge(user_config, local_config);
         let node_cmd = merged.get_command("node").unwrap();
-        let with_template = merged.merge_with_template(node_cmd);
+        let with_template = merged.merge_with_template(node_cmd).unwrap();
 
         // Should inherit from user's base model
         assert_eq!(with_template.share, vec!["user"]);
@@ -1065,6 +2824,80 @@ mod tests {
         assert!(commands.contains_key("node"));
     }
 
+    #[test]
+    fn test_conflicting_overrides_reports_differing_fields() {
+        let user_config = Config::from_yaml(indoc! {"
+            node:
+              enabled: true
+              share:
+                - user
+              bind:
+                - /opt/node:/opt/node
+        "})
+        .unwrap();
+
+        let local_config = Config::from_yaml(indoc! {"
+            node:
+              enabled: false
+              share:
+                - network
+              bind:
+                - /opt/node:/opt/node
+        "})
+        .unwrap();
+
+        let conflicts = Config::conflicting_overrides(&user_config, &local_config);
+
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts.iter().any(|c| c.field == "share" && c.command == "node"));
+        assert!(conflicts.iter().any(|c| c.field == "enabled" && c.command == "node"));
+        assert!(!conflicts.iter().any(|c| c.field == "bind"));
+    }
+
+    #[test]
+    fn test_conflicting_overrides_ignores_entries_only_in_one_layer() {
+        let user_config = Config::from_yaml(indoc! {"
+            node:
+              enabled: true
+              share:
+                - user
+        "})
+        .unwrap();
+
+        let local_config = Config::from_yaml(indoc! {"
+            python:
+              enabled: true
+              share:
+                - network
+        "})
+        .unwrap();
+
+        assert!(Config::conflicting_overrides(&user_config, &local_config).is_empty());
+    }
+
+    #[test]
+    fn test_conflicting_overrides_ignores_model_entries() {
+        let user_config = Config::from_yaml(indoc! {"
+            base:
+              type: model
+              share:
+                - user
+        "})
+        .unwrap();
+
+        let local_config = Config::from_yaml(indoc! {"
+            base:
+              type: model
+              share:
+                - network
+        "})
+        .unwrap();
+
+        // `base` differs between the two layers, but it's a `type: model`
+        // template, not a command, so it shouldn't be reported as one.
+        assert!(Config::conflicting_overrides(&user_config, &local_config).is_empty());
+    }
+
     #[test]
     fn test_override_defaults_to_false() {
         let config = Config::from_yaml(indoc! {"
@@ -1297,7 +3130,7 @@ mod tests {
         .unwrap();
 
         let node_cmd = config.get_command("node").unwrap();
-        let merged = config.merge_with_template(node_cmd);
+        let merged = config.merge_with_template(node_cmd).unwrap();
 
         // Should have shares from both models
         assert!(merged.share.contains(&"user".to_string()));
@@ -1331,7 +3164,7 @@ mod tests {
         .unwrap();
 
         let node_cmd = config.get_command("node").unwrap();
-        let merged = config.merge_with_template(node_cmd);
+        let merged = config.merge_with_template(node_cmd).unwrap();
 
         // Later model's env should override earlier model's env
         assert_eq!(merged.env.get("KEY"), Some(&"override_value".to_string()));
@@ -1360,7 +3193,7 @@ mod tests {
         .unwrap();
 
         let node_cmd = config.get_command("node").unwrap();
-        let merged = config.merge_with_template(node_cmd);
+        let merged = config.merge_with_template(node_cmd).unwrap();
 
         // Command's own env should override all models
         assert_eq!(merged.env.get("KEY"), Some(&"command_value".to_string()));
@@ -1385,7 +3218,7 @@ mod tests {
         .unwrap();
 
         let node_cmd = config.get_command("node").unwrap();
-        let merged = config.merge_with_template(node_cmd);
+        let merged = config.merge_with_template(node_cmd).unwrap();
 
         // Should apply base and network, skip nonexistent
         assert!(merged.share.contains(&"user".to_string()));
@@ -1403,7 +3236,7 @@ mod tests {
         .unwrap();
 
         let node_cmd = config.get_command("node").unwrap();
-        let merged = config.merge_with_template(node_cmd);
+        let merged = config.merge_with_template(node_cmd).unwrap();
 
         // Should just have command's own settings
         assert_eq!(merged.share, vec!["user"]);
@@ -1422,7 +3255,508 @@ mod tests {
         let node_cmd = config.get_command("node").unwrap();
         assert_eq!(node_cmd.extends, Vec::<String>::new());
 
-        let merged = config.merge_with_template(node_cmd);
+        let merged = config.merge_with_template(node_cmd).unwrap();
         assert_eq!(merged.share, vec!["user"]);
     }
+
+    #[test]
+    fn test_resolve_command_passes_through_plain_command() {
+        let config = Config::from_yaml(indoc! {"
+            node:
+              share:
+                - user
+        "})
+        .unwrap();
+
+        let (name, entry, args) = config.resolve_command("node").unwrap().unwrap();
+        assert_eq!(name, "node");
+        assert_eq!(entry.share, vec!["user"]);
+        assert!(args.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_command_alias_expands_to_target() {
+        let config = Config::from_yaml(indoc! {"
+            node:
+              share:
+                - user
+              bind:
+                - ~/.npm:~/.npm
+
+            node-ci:
+              type: alias
+              alias: node
+              args:
+                - --ci
+              env:
+                CI: \"true\"
+        "})
+        .unwrap();
+
+        let (name, entry, args) = config.resolve_command("node-ci").unwrap().unwrap();
+        assert_eq!(name, "node");
+        assert_eq!(args, vec!["--ci"]);
+        // Alias's own fields are layered on top of the target's.
+        assert_eq!(entry.bind, vec!["~/.npm:~/.npm"]);
+        assert_eq!(entry.env.get("CI"), Some(&"true".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_command_alias_preserves_target_extends() {
+        let config = Config::from_yaml(indoc! {"
+            base:
+              type: model
+              share:
+                - user
+
+            node:
+              extends: base
+              bind:
+                - ~/.npm:~/.npm
+
+            node-ci:
+              type: alias
+              alias: node
+              args:
+                - --ci
+        "})
+        .unwrap();
+
+        let (_, entry, _) = config.resolve_command("node-ci").unwrap().unwrap();
+        // The alias doesn't declare its own `extends`, so the target's
+        // model chain must survive the merge, not be wiped out by it.
+        let merged = config.merge_with_template(entry).unwrap();
+        assert_eq!(merged.share, vec!["user"]);
+    }
+
+    #[test]
+    fn test_resolve_command_transitive_alias_accumulates_args() {
+        let config = Config::from_yaml(indoc! {"
+            node:
+              share:
+                - user
+
+            node-ci:
+              type: alias
+              alias: node
+              args:
+                - --ci
+
+            node-ci-verbose:
+              type: alias
+              alias: node-ci
+              args:
+                - --verbose
+        "})
+        .unwrap();
+
+        let (name, _, args) = config.resolve_command("node-ci-verbose").unwrap().unwrap();
+        assert_eq!(name, "node");
+        assert_eq!(args, vec!["--ci", "--verbose"]);
+    }
+
+    #[test]
+    fn test_resolve_command_alias_cycle_is_rejected() {
+        let config = Config::from_yaml(indoc! {"
+            a:
+              type: alias
+              alias: b
+
+            b:
+              type: alias
+              alias: a
+        "})
+        .unwrap();
+
+        let err = config.resolve_command("a").unwrap_err();
+        assert!(err.to_string().contains("Cycle detected"));
+    }
+
+    #[test]
+    fn test_resolve_command_alias_to_nonexistent_target() {
+        let config = Config::from_yaml(indoc! {"
+            node-ci:
+              type: alias
+              alias: node
+        "})
+        .unwrap();
+
+        let err = config.resolve_command("node-ci").unwrap_err();
+        assert!(err.to_string().contains("nonexistent"));
+    }
+
+    #[test]
+    fn test_resolve_command_alias_to_disabled_target_is_rejected() {
+        let config = Config::from_yaml(indoc! {"
+            node:
+              enabled: false
+              share:
+                - user
+
+            node-ci:
+              type: alias
+              alias: node
+        "})
+        .unwrap();
+
+        let err = config.resolve_command("node-ci").unwrap_err();
+        assert!(err.to_string().contains("disabled"));
+        assert!(err.to_string().contains("node"));
+    }
+
+    #[test]
+    fn test_resolve_command_unknown_name_is_none() {
+        let config = Config::from_yaml(indoc! {"
+            node:
+              share:
+                - user
+        "})
+        .unwrap();
+
+        assert!(config.resolve_command("ruby").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_command_names_includes_aliases() {
+        let config = Config::from_yaml(indoc! {"
+            base:
+              type: model
+
+            node:
+              share:
+                - user
+
+            node-ci:
+              type: alias
+              alias: node
+        "})
+        .unwrap();
+
+        let names = config.command_names();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"node".to_string()));
+        assert!(names.contains(&"node-ci".to_string()));
+        assert!(!names.contains(&"base".to_string()));
+    }
+
+    #[test]
+    fn test_check_policy_no_policy_is_always_ok() {
+        let config = Config::from_yaml(indoc! {"
+            node:
+              share:
+                - network
+        "})
+        .unwrap();
+
+        let node_cmd = config.get_command("node").unwrap();
+        assert!(config.check_policy(&node_cmd).is_ok());
+    }
+
+    #[test]
+    fn test_check_policy_denies_matching_share() {
+        let config = Config::from_yaml(indoc! {"
+            policy:
+              rules:
+                - effect: deny
+                  share: network
+
+            node:
+              share:
+                - network
+        "})
+        .unwrap();
+
+        let node_cmd = config.get_command("node").unwrap();
+        let violation = config.check_policy(&node_cmd).unwrap_err();
+        assert_eq!(violation.field, PolicyField::Share);
+        assert_eq!(violation.value, "network");
+        assert!(violation.to_string().contains("denies share 'network'"));
+    }
+
+    #[test]
+    fn test_check_policy_denies_bind_by_prefix() {
+        let config = Config::from_yaml(indoc! {"
+            policy:
+              rules:
+                - effect: deny
+                  bind: /etc
+
+            node:
+              bind:
+                - /etc/passwd:/etc/passwd
+        "})
+        .unwrap();
+
+        let node_cmd = config.get_command("node").unwrap();
+        let violation = config.check_policy(&node_cmd).unwrap_err();
+        assert_eq!(violation.field, PolicyField::Bind);
+        assert_eq!(violation.value, "/etc/passwd");
+    }
+
+    #[test]
+    fn test_check_policy_allow_does_not_override_deny() {
+        let config = Config::from_yaml(indoc! {"
+            policy:
+              rules:
+                - effect: allow
+                  share: network
+                - effect: deny
+                  share: network
+
+            node:
+              share:
+                - network
+        "})
+        .unwrap();
+
+        let node_cmd = config.get_command("node").unwrap();
+        assert!(config.check_policy(&node_cmd).is_err());
+    }
+
+    #[test]
+    fn test_check_policy_allows_unmatched_values() {
+        let config = Config::from_yaml(indoc! {"
+            policy:
+              rules:
+                - effect: deny
+                  share: network
+
+            node:
+              share:
+                - user
+        "})
+        .unwrap();
+
+        let node_cmd = config.get_command("node").unwrap();
+        assert!(config.check_policy(&node_cmd).is_ok());
+    }
+
+    #[test]
+    fn test_enforce_policy_rejects_by_default() {
+        let config = Config::from_yaml(indoc! {"
+            policy:
+              rules:
+                - effect: deny
+                  share: network
+
+            node:
+              share:
+                - network
+        "})
+        .unwrap();
+
+        let node_cmd = config.get_command("node").unwrap();
+        let err = config.enforce_policy(node_cmd).unwrap_err();
+        assert!(err.to_string().contains("denies share 'network'"));
+    }
+
+    #[test]
+    fn test_enforce_policy_strips_when_configured() {
+        let config = Config::from_yaml(indoc! {"
+            policy:
+              on_violation: strip
+              rules:
+                - effect: deny
+                  share: network
+                - effect: deny
+                  bind: /etc
+
+            node:
+              share:
+                - user
+                - network
+              bind:
+                - /etc/passwd:/etc/passwd
+                - ~/.npm:~/.npm
+        "})
+        .unwrap();
+
+        let node_cmd = config.get_command("node").unwrap();
+        let sanitized = config.enforce_policy(node_cmd).unwrap();
+        assert_eq!(sanitized.share, vec!["user"]);
+        assert_eq!(sanitized.bind, vec!["~/.npm:~/.npm"]);
+    }
+
+    #[test]
+    fn test_policy_merges_rules_across_layers() {
+        // The base/org layer's deny rule must survive even though the
+        // higher-priority local layer ships its own (non-overlapping) policy.
+        let base = Config::from_yaml(indoc! {"
+            policy:
+              rules:
+                - effect: deny
+                  share: network
+
+            node:
+              share:
+                - network
+        "})
+        .unwrap();
+
+        let local = Config::from_yaml(indoc! {"
+            policy:
+              rules:
+                - effect: deny
+                  bind: /etc
+        "})
+        .unwrap();
+
+        let merged = Config::merge(base, local);
+        let node_cmd = merged.get_command("node").unwrap();
+
+        assert!(merged.check_policy(&node_cmd).is_err());
+    }
+
+    #[test]
+    fn test_unknown_policy_field_rejected() {
+        let err = Config::from_yaml(indoc! {"
+            policy:
+              rule: []
+        "})
+        .unwrap_err();
+        assert!(err.to_string().contains("rule"));
+    }
+
+    #[test]
+    fn test_unknown_policy_rule_field_rejected() {
+        let err = Config::from_yaml(indoc! {"
+            policy:
+              rules:
+                - effect: deny
+                  shares: network
+        "})
+        .unwrap_err();
+        assert!(err.to_string().contains("shares"));
+    }
+
+    #[test]
+    fn test_unknown_entry_type_rejected() {
+        let err = Config::from_yaml(indoc! {"
+            node:
+              type: comand
+        "})
+        .unwrap_err();
+        assert!(err.to_string().contains("comand"));
+        assert!(err.to_string().contains("node"));
+    }
+
+    #[test]
+    fn test_unknown_profile_entry_type_rejected() {
+        let err = Config::from_yaml(indoc! {"
+            node:
+              share:
+                - user
+              profiles:
+                dev:
+                  type: bogus
+        "})
+        .unwrap_err();
+        assert!(err.to_string().contains("bogus"));
+        assert!(err.to_string().contains("node.profiles.dev"));
+    }
+
+    #[test]
+    fn test_get_command_checked_distinguishes_missing_from_wrong_type() {
+        let config = Config::from_yaml(indoc! {"
+            base:
+              type: model
+
+            node:
+              share:
+                - user
+        "})
+        .unwrap();
+
+        assert!(config.get_command_checked("node").is_ok());
+
+        let missing = config.get_command_checked("ruby").unwrap_err();
+        assert!(matches!(missing, ConfigError::NotFound(_)));
+
+        let wrong_type = config.get_command_checked("base").unwrap_err();
+        assert!(matches!(wrong_type, ConfigError::WrongType { .. }));
+        assert!(wrong_type.to_string().contains("model"));
+
+        // The plain `Option`-returning API still folds both into `None`.
+        assert!(config.get_command("ruby").is_none());
+        assert!(config.get_command("base").is_none());
+    }
+
+    #[test]
+    fn test_config_result_ext_collapses_only_not_found() {
+        let config = Config::from_yaml(indoc! {"
+            base:
+              type: model
+        "})
+        .unwrap();
+
+        assert!(config.get_command_checked("ruby").not_found_is_none().unwrap().is_none());
+
+        let err = config.get_command_checked("base").not_found_is_none().unwrap_err();
+        assert!(matches!(err, ConfigError::WrongType { .. }));
+    }
+
+    #[test]
+    fn test_resolve_command_on_model_reports_wrong_type() {
+        let config = Config::from_yaml(indoc! {"
+            base:
+              type: model
+              share:
+                - user
+        "})
+        .unwrap();
+
+        let err = config.resolve_command("base").unwrap_err();
+        assert!(err.to_string().contains("model"));
+    }
+
+    #[test]
+    fn test_new_command_is_enabled_command_type() {
+        let entry = Entry::new_command();
+        assert_eq!(entry.entry_type, EntryType::Command);
+        assert!(entry.enabled);
+        assert!(entry.bind.is_empty());
+    }
+
+    #[test]
+    fn test_set_field_appends_to_list_fields() {
+        let mut entry = Entry::new_command();
+        entry.set_field("bind", "/tmp:/tmp").unwrap();
+        entry.set_field("bind", "/var:/var").unwrap();
+        assert_eq!(entry.bind, vec!["/tmp:/tmp", "/var:/var"]);
+    }
+
+    #[test]
+    fn test_set_field_replaces_scalar_fields() {
+        let mut entry = Entry::new_command();
+        entry.set_field("chdir", "/app").unwrap();
+        entry.set_field("chdir", "/other").unwrap();
+        assert_eq!(entry.chdir, Some("/other".to_string()));
+    }
+
+    #[test]
+    fn test_set_field_parses_bool_fields() {
+        let mut entry = Entry::new_command();
+        entry.set_field("enabled", "false").unwrap();
+        assert!(!entry.enabled);
+
+        let err = entry.set_field("enabled", "nope").unwrap_err();
+        assert!(err.to_string().contains("enabled"));
+    }
+
+    #[test]
+    fn test_set_field_env_requires_key_value_form() {
+        let mut entry = Entry::new_command();
+        entry.set_field("env", "NODE_ENV=production").unwrap();
+        assert_eq!(entry.env.get("NODE_ENV"), Some(&"production".to_string()));
+
+        let err = entry.set_field("env", "malformed").unwrap_err();
+        assert!(err.to_string().contains("KEY=VALUE"));
+    }
+
+    #[test]
+    fn test_set_field_rejects_unknown_and_nested_fields() {
+        let mut entry = Entry::new_command();
+        assert!(entry.set_field("nope", "x").is_err());
+        assert!(entry.set_field("seccomp", "x").is_err());
+    }
 }
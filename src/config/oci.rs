@@ -0,0 +1,244 @@
+// Copyright (C) 2025 Pierre Le Gall
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Import an OCI runtime-spec `config.json` (as produced by `runc`, `crun`,
+//! and friends) into an `Entry`, so an existing container bundle can be run
+//! through sheld instead of hand-writing a YAML entry from scratch.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use super::{blank_model_entry, Entry};
+
+/// Namespace types `bwrap::WrappedCommandBuilder` knows how to unshare
+/// (mirrors `bwrap::NAMESPACES`; duplicated here rather than shared, since
+/// `config` sits below `bwrap` in the dependency graph). An OCI namespace
+/// type outside this list (e.g. `mount`) has no bwrap equivalent and is
+/// ignored.
+const KNOWN_NAMESPACES: [&str; 6] = ["user", "pid", "network", "ipc", "uts", "cgroup"];
+
+/// Bubblewrap-relevant subset of an OCI runtime spec. Everything not
+/// mapped to an `Entry` field (`root`, `hooks`, `annotations`, ...) is
+/// ignored.
+#[derive(Debug, Deserialize)]
+struct OciSpec {
+    #[serde(default)]
+    process: Option<OciProcess>,
+    #[serde(default)]
+    mounts: Vec<OciMount>,
+    #[serde(default)]
+    linux: Option<OciLinux>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OciProcess {
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    cwd: Option<String>,
+    #[serde(default)]
+    env: Vec<String>,
+    #[serde(default)]
+    capabilities: Option<OciCapabilities>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OciCapabilities {
+    #[serde(default)]
+    bounding: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OciMount {
+    #[serde(default)]
+    destination: String,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(default, rename = "type")]
+    mount_type: Option<String>,
+    #[serde(default)]
+    options: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OciLinux {
+    #[serde(default)]
+    namespaces: Vec<OciNamespace>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OciNamespace {
+    #[serde(rename = "type")]
+    namespace_type: String,
+}
+
+impl Entry {
+    /// Parse an OCI runtime-spec `config.json` at `path` and translate it
+    /// into an `Entry`. See `Entry::from_oci_json` for the field mapping.
+    pub fn from_oci_spec<P: AsRef<Path>>(path: P) -> Result<Entry> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read OCI spec: {:?}", path))?;
+        Entry::from_oci_json(&json).with_context(|| format!("Failed to parse OCI spec: {:?}", path))
+    }
+
+    /// Translate an OCI runtime-spec JSON document into an `Entry`:
+    /// `process.args`/`cwd`/`env` become the entry's fixed `args`, `chdir`,
+    /// and `env`; a `bind`-type mount becomes `bind` (or `ro_bind` when its
+    /// `options` contain `ro`); `linux.namespaces` becomes the inverse of
+    /// `share` (a namespace listed there is unshared, one left out is
+    /// shared); and `process.capabilities.bounding` becomes `cap`.
+    pub fn from_oci_json(json: &str) -> Result<Entry> {
+        let spec: OciSpec = serde_json::from_str(json).context("Invalid OCI runtime spec")?;
+
+        let mut entry = blank_model_entry();
+
+        if let Some(process) = spec.process {
+            entry.args = process.args;
+            entry.chdir = process.cwd;
+            entry.env = parse_env(&process.env);
+            if let Some(capabilities) = process.capabilities {
+                entry.cap = capabilities.bounding;
+            }
+        }
+
+        for mount in &spec.mounts {
+            if mount.mount_type.as_deref() != Some("bind") {
+                continue;
+            }
+            let Some(source) = &mount.source else { continue };
+
+            let bind_spec = format!("{}:{}", source, mount.destination);
+            if mount.options.iter().any(|option| option == "ro") {
+                entry.ro_bind.push(bind_spec);
+            } else {
+                entry.bind.push(bind_spec);
+            }
+        }
+
+        let declared_namespaces: HashSet<String> = spec
+            .linux
+            .map(|linux| linux.namespaces)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|namespace| namespace.namespace_type)
+            .collect();
+        entry.share = KNOWN_NAMESPACES
+            .iter()
+            .copied()
+            .filter(|namespace| !declared_namespaces.contains(*namespace))
+            .map(|namespace| namespace.to_string())
+            .collect();
+
+        Ok(entry)
+    }
+}
+
+/// Parse OCI `process.env`'s `"KEY=VALUE"` entries into a map. Entries
+/// without an `=` are skipped rather than rejected, matching the
+/// permissive spirit of the rest of the config loader.
+fn parse_env(vars: &[String]) -> HashMap<String, String> {
+    vars.iter()
+        .filter_map(|var| var.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_from_oci_json_maps_process_fields() {
+        let entry = Entry::from_oci_json(indoc! {r#"
+            {
+                "process": {
+                    "args": ["node", "server.js"],
+                    "cwd": "/app",
+                    "env": ["NODE_ENV=production", "malformed"]
+                }
+            }
+        "#})
+        .unwrap();
+
+        assert_eq!(entry.args, vec!["node", "server.js"]);
+        assert_eq!(entry.chdir, Some("/app".to_string()));
+        assert_eq!(entry.env.get("NODE_ENV"), Some(&"production".to_string()));
+        assert_eq!(entry.env.len(), 1);
+    }
+
+    #[test]
+    fn test_from_oci_json_maps_bind_mounts() {
+        let entry = Entry::from_oci_json(indoc! {r#"
+            {
+                "mounts": [
+                    {"destination": "/data", "source": "/host/data", "type": "bind", "options": ["rbind"]},
+                    {"destination": "/usr", "source": "/host/usr", "type": "bind", "options": ["ro", "rbind"]},
+                    {"destination": "/proc", "type": "proc"}
+                ]
+            }
+        "#})
+        .unwrap();
+
+        assert_eq!(entry.bind, vec!["/host/data:/data"]);
+        assert_eq!(entry.ro_bind, vec!["/host/usr:/usr"]);
+    }
+
+    #[test]
+    fn test_from_oci_json_maps_capabilities() {
+        let entry = Entry::from_oci_json(indoc! {r#"
+            {
+                "process": {
+                    "capabilities": {
+                        "bounding": ["CAP_NET_BIND_SERVICE", "CAP_SYS_ADMIN"]
+                    }
+                }
+            }
+        "#})
+        .unwrap();
+
+        assert_eq!(entry.cap, vec!["CAP_NET_BIND_SERVICE", "CAP_SYS_ADMIN"]);
+    }
+
+    #[test]
+    fn test_from_oci_json_namespaces_invert_to_share() {
+        let entry = Entry::from_oci_json(indoc! {r#"
+            {
+                "linux": {
+                    "namespaces": [
+                        {"type": "pid"},
+                        {"type": "network"},
+                        {"type": "mount"}
+                    ]
+                }
+            }
+        "#})
+        .unwrap();
+
+        // Declared namespaces are unshared, so they're absent from `share`.
+        assert!(!entry.share.contains(&"pid".to_string()));
+        assert!(!entry.share.contains(&"network".to_string()));
+        // Namespaces the spec didn't declare are left shared.
+        assert!(entry.share.contains(&"user".to_string()));
+        assert!(entry.share.contains(&"ipc".to_string()));
+        assert!(entry.share.contains(&"uts".to_string()));
+        assert!(entry.share.contains(&"cgroup".to_string()));
+    }
+
+    #[test]
+    fn test_from_oci_json_no_linux_section_shares_everything() {
+        let entry = Entry::from_oci_json("{}").unwrap();
+
+        assert_eq!(entry.share.len(), KNOWN_NAMESPACES.len());
+    }
+
+    #[test]
+    fn test_from_oci_json_rejects_invalid_json() {
+        let err = Entry::from_oci_json("not json").unwrap_err();
+        assert!(err.to_string().contains("Invalid OCI runtime spec"));
+    }
+}
@@ -2,12 +2,15 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 mod cli;
+mod profiles;
 mod shell_hooks;
 
 use anyhow::{Context, Result, bail};
-use clap::Parser;
+use clap::{CommandFactory, Parser, ValueEnum};
+use clap_complete::{generate, Shell as CompletionShell};
 
-use cli::{Action, Cli};
+use cli::{Action, Cli, ConfigAction};
+use profiles::Profile;
 use shell_hooks::Shell;
 use shwrap::bwrap::WrappedCommandBuilder;
 use shwrap::config::{self, loader::ConfigLoader};
@@ -15,21 +18,31 @@ use shwrap::config::{self, loader::ConfigLoader};
 fn main() -> Result<()> {
     let input = Cli::parse();
 
+    if input.verbose {
+        eprintln!(
+            "color: {}",
+            if input.use_color() { "on" } else { "off" }
+        );
+    }
+
     match input.action {
-        Action::Init => {
-            initialize_config()?;
+        Action::Init { profile } => {
+            initialize_config(profile)?;
         }
-        Action::Validate { path, silent } => {
-            validate_config(path, silent)?;
+        Action::Validate { path, silent, show_origin, strict } => {
+            validate_config(path.or_else(|| input.config.clone()), silent, show_origin, strict)?;
         }
-        Action::List { simple } => {
-            list_commands(simple)?;
+        Action::List { simple, show_origin } => {
+            let config = resolve_config(input.config.as_deref(), input.verbose)?;
+            list_commands(config, simple, show_origin)?;
         }
-        Action::Show { command, args } => {
-            show_command(&command, &args)?;
+        Action::Show { command, args, show_origin, profile } => {
+            let config = resolve_config(input.config.as_deref(), input.verbose)?;
+            show_command(config, &command, &args, show_origin, profile.as_deref())?;
         }
-        Action::Wrap { command, args } => {
-            wrap_command(&command, &args)?;
+        Action::Wrap { command, args, profile } => {
+            let config = resolve_config(input.config.as_deref(), input.verbose)?;
+            wrap_command(config, &command, &args, profile.as_deref())?;
         }
         Action::Bypass { command, args } => {
             bypass_command(&command, &args)?;
@@ -37,52 +50,164 @@ fn main() -> Result<()> {
         Action::Activate { shell } => {
             print_shell_hook(&shell)?;
         }
+        Action::Completions { shell } => {
+            print_completions(&shell)?;
+        }
+        Action::Explain { command, json } => {
+            explain_config(command, json)?;
+        }
         Action::Check { command, silent } => {
-            check_command(&command, silent)?;
+            let config = resolve_config(input.config.as_deref(), input.verbose)?;
+            check_command(config, &command, silent)?;
         }
+        Action::Config { action } => match action {
+            ConfigAction::Set { command, key, value } => {
+                config_set(input.config.as_deref(), &command, &key, &value)?;
+            }
+            ConfigAction::Edit => {
+                config_edit(input.config.as_deref())?;
+            }
+        },
     }
 
     Ok(())
 }
 
-fn wrap_command(command: &str, args: &[String]) -> Result<()> {
-    let config = ConfigLoader::load()?.context("No configuration found")?;
+/// Shown whenever a config-dependent action can't find any `.sheld.yaml`.
+const NO_CONFIG_HINT: &str = "No configuration found, run `sheld init` to create one";
 
-    let cmd_config = config
-        .get_command(command)
-        .context(format!("No configuration found for command '{}'", command))?;
+/// Resolve the config to use for this invocation: an explicit `--config`
+/// override takes precedence over the usual local/user hierarchy lookup.
+fn resolve_config(override_path: Option<&str>, verbose: bool) -> Result<Option<config::Config>> {
+    if let Some(path) = override_path {
+        if verbose {
+            eprintln!("Loading config from --config override: {}", path);
+        }
+        return Ok(Some(ConfigLoader::load_file_with_includes(path)?));
+    }
+
+    if verbose {
+        match ConfigLoader::get_config_file()? {
+            Some(path) => eprintln!("Loading config from: {:?}", path),
+            None => eprintln!("No config file found in hierarchy"),
+        }
+    }
+
+    ConfigLoader::load()
+}
+
+/// Build the "command not found" error for `wrap_command`/`show_command`/
+/// `check_command`, with a Levenshtein "did you mean" suggestion when one
+/// of `config`'s configured command names is close enough. Computed here,
+/// after `resolve_config` has already applied any `--config` override,
+/// rather than in a clap `value_parser` (which only ever sees the default
+/// `.sheld.yaml` hierarchy and can't see the override, and would also run
+/// before a `--silent` flag could suppress it).
+fn command_not_found_message(config: &config::Config, command: &str) -> String {
+    let names = config.command_names();
+    match cli::suggest::closest_match(command, &names) {
+        Some(candidate) => format!(
+            "command '{}' not found in configuration, did you mean '{}'?",
+            command, candidate
+        ),
+        None => format!("command '{}' not found in configuration", command),
+    }
+}
+
+/// Apply a `--profile` selection on top of an already-resolved entry, via
+/// `Entry::apply_profile`. `None` leaves the entry unchanged; a name not
+/// present in the entry's `profiles:` map is an error rather than silently
+/// falling back to the base entry, so a typo doesn't run unsandboxed.
+fn apply_selected_profile(entry: config::Entry, profile: Option<&str>) -> Result<config::Entry> {
+    let Some(name) = profile else {
+        return Ok(entry);
+    };
+
+    let overlay = entry
+        .profiles
+        .get(name)
+        .cloned()
+        .with_context(|| format!("profile '{}' not found on this command", name))?;
+
+    Ok(config::Entry::apply_profile(entry, overlay))
+}
+
+fn wrap_command(config: Option<config::Config>, command: &str, args: &[String], profile: Option<&str>) -> Result<()> {
+    let config = config.context(NO_CONFIG_HINT)?;
+
+    let (resolved_name, cmd_config, fixed_args) = match config.resolve_command(command)? {
+        Some(result) => result,
+        None => bail!(command_not_found_message(&config, command)),
+    };
 
     if !cmd_config.enabled {
         bail!("Command '{}' is disabled in configuration", command);
     }
 
-    let merged_config = config.merge_with_base(cmd_config);
-    let builder = WrappedCommandBuilder::new(merged_config);
+    let merged_config = config.merge_with_base(cmd_config)?;
+    let merged_config = apply_selected_profile(merged_config, profile)?;
+    let merged_config = config.enforce_policy(merged_config)?;
+    let builder = WrappedCommandBuilder::new(merged_config)?;
 
-    let exit_code = builder.exec(command, args)?;
+    let all_args: Vec<String> = fixed_args.into_iter().chain(args.iter().cloned()).collect();
+    let exit_code = builder.exec(&resolved_name, &all_args)?;
 
     std::process::exit(exit_code)
 }
 
-fn list_commands(simple: bool) -> Result<()> {
-    let config = ConfigLoader::load()?.context("No configuration found")?;
+/// The config file that last defined `name` across `layers`, in merge
+/// order (the last layer to define an entry is the one that took effect).
+/// Shared by `list --show-origin`, `show --show-origin`,
+/// `validate --show-origin`, and `explain`.
+fn origin_of<'a>(layers: &'a [(config::Source, std::path::PathBuf, config::Config)], name: &str) -> &'a std::path::Path {
+    layers
+        .iter()
+        .rev()
+        .find(|(_, _, c)| c.entries.contains_key(name))
+        .map(|(_, path, _)| path.as_path())
+        .unwrap_or_else(|| std::path::Path::new("<unknown>"))
+}
+
+fn list_commands(config: Option<config::Config>, simple: bool, show_origin: bool) -> Result<()> {
+    let config = config.context(NO_CONFIG_HINT)?;
+
+    let layers = if show_origin { ConfigLoader::load_layers()? } else { Vec::new() };
+    let origin_suffix = |name: &str| -> String {
+        if show_origin {
+            format!(" (from {:?})", origin_of(&layers, name))
+        } else {
+            String::new()
+        }
+    };
 
     // Sort commands alphabetically
     let commands_map = config.get_commands();
     let mut commands: Vec<_> = commands_map.iter().collect();
     commands.sort_by_key(|(name, _)| *name);
 
+    // Aliases are invokable from the command line just like the commands
+    // they point to, so shell hooks that register completions from `list
+    // --simple` need to see them too.
+    let aliases_map = config.get_entries_with(|e| e.entry_type == config::EntryType::Alias);
+    let mut aliases: Vec<_> = aliases_map.iter().collect();
+    aliases.sort_by_key(|(name, _)| *name);
+
     if simple {
-        for (name, cmd_config) in commands {
+        for (name, cmd_config) in &commands {
             if cmd_config.enabled {
-                println!("{}", name);
+                println!("{}{}", name, origin_suffix(name));
+            }
+        }
+        for (name, alias) in &aliases {
+            if alias.enabled {
+                println!("{}{}", name, origin_suffix(name));
             }
         }
     } else {
         println!("Active command configurations:");
-        for (name, cmd_config) in commands {
+        for (name, cmd_config) in &commands {
             if cmd_config.enabled {
-                println!("\n{}:", name);
+                println!("\n{}{}:", name, origin_suffix(name));
                 if !cmd_config.share.is_empty() {
                     println!("  share: {}", cmd_config.share.join(", "));
                 }
@@ -96,24 +221,154 @@ fn list_commands(simple: bool) -> Result<()> {
                 }
             }
         }
+
+        if !aliases.is_empty() {
+            println!("\nAliases:");
+            for (name, alias) in &aliases {
+                if alias.enabled {
+                    let target = alias.alias.as_deref().unwrap_or("?");
+                    println!("  {} -> {}{}", name, target, origin_suffix(name));
+                }
+            }
+        }
     }
 
     Ok(())
 }
 
-fn show_command(command: &str, args: &[String]) -> Result<()> {
-    let config = ConfigLoader::load()?.context("No configuration found")?;
+/// Print the merged effective configuration, annotating each command with
+/// the file it was last defined or overridden in.
+fn explain_config(command: Option<String>, json: bool) -> Result<()> {
+    let layers = ConfigLoader::load_layers()?;
+    if layers.is_empty() {
+        bail!(NO_CONFIG_HINT);
+    }
+
+    let merged = layers
+        .iter()
+        .map(|(_, _, c)| c.clone())
+        .reduce(config::Config::merge)
+        .expect("layers is non-empty");
+
+    let mut commands: Vec<_> = merged.get_commands().into_iter().collect();
+    if let Some(name) = &command {
+        commands.retain(|(n, _)| n == name);
+        if commands.is_empty() {
+            bail!("No configuration found for command '{}'", name);
+        }
+    }
+    commands.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let source_layers: Vec<(config::Source, config::Config)> =
+        layers.iter().map(|(source, _, c)| (source.clone(), c.clone())).collect();
+
+    if json {
+        let mut items = Vec::new();
+        for (name, entry) in &commands {
+            items.push(format!(
+                "{{\"name\":{},\"source\":{},\"enabled\":{},\"share\":[{}],\"bind\":[{}]}}",
+                json_string(name),
+                json_string(&origin_of(&layers, name).display().to_string()),
+                entry.enabled,
+                entry.share.iter().map(|s| json_string(s)).collect::<Vec<_>>().join(","),
+                entry.bind.iter().map(|s| json_string(s)).collect::<Vec<_>>().join(","),
+            ));
+        }
+        println!("[{}]", items.join(","));
+    } else {
+        for (name, entry) in &commands {
+            println!("{} (from {:?}):", name, origin_of(&layers, name));
+            println!("  enabled: {}", entry.enabled);
+
+            let resolved = config::Config::explain_entry(&source_layers, name)?;
+            if let Some(resolved) = resolved {
+                print_annotated_field("share", &resolved.share);
+                print_annotated_field("bind", &resolved.bind);
+
+                if !resolved.env.is_empty() {
+                    let mut env: Vec<_> = resolved.env.iter().collect();
+                    env.sort_by_key(|(k, _)| k.clone());
+                    for (key, annotated) in env {
+                        println!("  env: {}={} (from {})", key, annotated.value, describe_source(&annotated.source));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print one line per annotated value in a field, naming its origin.
+fn print_annotated_field(field: &str, values: &[config::AnnotatedValue]) {
+    for annotated in values {
+        println!("  {}: {} (from {})", field, annotated.value, describe_source(&annotated.source));
+    }
+}
+
+/// Human-readable label for a `Source`, for `sheld explain` output.
+fn describe_source(source: &config::Source) -> String {
+    match source {
+        config::Source::User => "user config".to_string(),
+        config::Source::Local => "local config".to_string(),
+        config::Source::Env => "SHWRAP_CONFIG".to_string(),
+        config::Source::Model(name) => format!("model '{}'", name),
+        config::Source::CommandSelf => "command".to_string(),
+    }
+}
+
+/// Minimal JSON string escaping for `explain --json` output.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
 
-    let cmd_config = config
-        .get_command(command)
-        .context(format!("No configuration found for command '{}'", command))?;
+fn show_command(
+    config: Option<config::Config>,
+    command: &str,
+    args: &[String],
+    show_origin: bool,
+    profile: Option<&str>,
+) -> Result<()> {
+    let config = config.context(NO_CONFIG_HINT)?;
+
+    let (resolved_name, cmd_config, fixed_args) = match config.resolve_command(command)? {
+        Some(result) => result,
+        None => bail!(command_not_found_message(&config, command)),
+    };
 
-    let merged_config = config.merge_with_base(cmd_config);
-    let builder = WrappedCommandBuilder::new(merged_config);
+    let enabled = cmd_config.enabled;
+    let merged_config = config.merge_with_base(cmd_config)?;
+    let merged_config = apply_selected_profile(merged_config, profile)?;
+    let merged_config = config.enforce_policy(merged_config)?;
+    let builder = WrappedCommandBuilder::new(merged_config)?;
 
-    let cmd_line = builder.show(command, args);
+    let all_args: Vec<String> = fixed_args.into_iter().chain(args.iter().cloned()).collect();
+    let cmd_line = builder.show(&resolved_name, &all_args);
     println!("{}", cmd_line);
 
+    if show_origin {
+        let layers = ConfigLoader::load_layers()?;
+        println!("enabled: {} (from {:?})", enabled, origin_of(&layers, &resolved_name));
+
+        let source_layers: Vec<(config::Source, config::Config)> =
+            layers.iter().map(|(source, _, c)| (source.clone(), c.clone())).collect();
+        if let Some(resolved) = config::Config::explain_entry(&source_layers, &resolved_name)? {
+            print_annotated_field("share", &resolved.share);
+            print_annotated_field("bind", &resolved.bind);
+        }
+    }
+
     Ok(())
 }
 
@@ -132,51 +387,205 @@ fn bypass_command(command: &str, args: &[String]) -> Result<()> {
     Err(anyhow::Error::from(error).context(format!("Failed to execute command '{}'", command)))
 }
 
-fn validate_config(path: Option<String>, silent: bool) -> Result<()> {
+/// Validate a single config file (an explicit `--config` path, or the one
+/// `ConfigLoader::get_config_file` finds). Note this only checks that one
+/// file parses; it doesn't merge in `SHWRAP_CONFIG` layers the way
+/// `resolve_config`/`ConfigLoader::load` do for `wrap`/`show`/`list`/`check` —
+/// those apply user config, then local config, then each `SHWRAP_CONFIG`
+/// path left-to-right, each layer merged on top of the last. The one
+/// exception is `--show-origin` without an explicit `--config`: since its
+/// whole point is naming which layer a command came from, it loads and
+/// merges the full `user`/`local`/`SHWRAP_CONFIG` stack instead.
+///
+/// Without an explicit `--config`, the user and local layers are also kept
+/// apart (regardless of `--show-origin`) so any command both define with
+/// conflicting `share`/`bind`/`enabled` can be reported: `Config::merge`
+/// would otherwise let the local value win silently. `--strict` turns that
+/// report into a hard failure instead of a warning.
+fn validate_config(path: Option<String>, silent: bool, show_origin: bool, strict: bool) -> Result<()> {
+    // `--show-origin` with no explicit override needs to see every layer
+    // (user, local, SHWRAP_CONFIG) to say which one a command came from,
+    // not just the single file this function otherwise validates; the
+    // same layers double as input to the conflict check below.
+    let layers = if path.is_none() { ConfigLoader::load_layers()? } else { Vec::new() };
+
     let config_path = if let Some(p) = path {
         std::path::PathBuf::from(p)
     } else {
-        ConfigLoader::get_config_file()?.context("No configuration found")?
+        ConfigLoader::get_config_file()?.context(NO_CONFIG_HINT)?
     };
 
-    let config = config::Config::from_file(&config_path)?;
+    let config = if show_origin && !layers.is_empty() {
+        layers
+            .iter()
+            .map(|(_, _, c)| c.clone())
+            .reduce(config::Config::merge)
+            .expect("layers is non-empty")
+    } else {
+        ConfigLoader::load_file_with_includes(&config_path)?
+    };
 
-    if silent {
-        return Ok(());
-    }
+    let user_config = layers.iter().find(|(source, _, _)| *source == config::Source::User);
+    let local_config = layers.iter().find(|(source, _, _)| *source == config::Source::Local);
+    let conflicts = match (user_config, local_config) {
+        (Some((_, _, user)), Some((_, _, local))) => config::Config::conflicting_overrides(user, local),
+        _ => Vec::new(),
+    };
 
-    println!("Configuration is valid: {:?}", config_path);
-    let commands_map = config.get_commands();
-    println!("Found {} command(s)", commands_map.len());
+    if !silent {
+        println!("Configuration is valid: {:?}", config_path);
+        let commands_map = config.get_commands();
+        println!("Found {} command(s)", commands_map.len());
 
-    // Sort commands alphabetically
-    let mut commands: Vec<_> = commands_map.iter().collect();
-    commands.sort_by_key(|(name, _)| *name);
+        // Sort commands alphabetically
+        let mut commands: Vec<_> = commands_map.iter().collect();
+        commands.sort_by_key(|(name, _)| *name);
+
+        for (name, cmd_config) in commands {
+            let origin = if show_origin {
+                if layers.is_empty() {
+                    format!(" (from {:?})", config_path)
+                } else {
+                    format!(" (from {:?})", origin_of(&layers, name))
+                }
+            } else {
+                String::new()
+            };
+
+            match cmd_config.enabled {
+                true => println!("  - {}{}", name, origin),
+                false => println!("  - {} (disabled){}", name, origin),
+            }
+        }
 
-    for (name, cmd_config) in commands {
-        match cmd_config.enabled {
-            true => println!("  - {}", name),
-            false => println!("  - {} (disabled)", name),
+        if !conflicts.is_empty() {
+            println!("\nConflicting overrides (user config vs local config):");
+            for conflict in &conflicts {
+                println!(
+                    "  - {} {}: user={:?} local={:?}",
+                    conflict.command, conflict.field, conflict.user_value, conflict.local_value
+                );
+            }
         }
     }
 
+    if strict && !conflicts.is_empty() {
+        bail!(
+            "{} command(s) have conflicting user/local overrides; run 'sheld validate' without --silent to see them",
+            conflicts.len()
+        );
+    }
+
     Ok(())
 }
 
-fn initialize_config() -> Result<()> {
+fn initialize_config(profile: Option<String>) -> Result<()> {
     use std::fs;
 
-    let template_content = include_str!("../examples/default.yaml");
-
     let config_path = ConfigLoader::local_config_name();
     if std::path::Path::new(config_path).exists() {
         bail!("{} file already exists in current directory", config_path);
     }
 
-    fs::write(config_path, template_content)
+    let profile = match profile {
+        Some(name) => Profile::from_name(&name).with_context(|| {
+            let available: Vec<_> = Profile::ALL.iter().map(|p| p.name()).collect();
+            format!(
+                "Unknown profile '{}', expected one of: {}",
+                name,
+                available.join(", ")
+            )
+        })?,
+        None => Profile::prompt().context("Failed to read profile choice")?,
+    };
+
+    fs::write(config_path, profile.template())
         .context(format!("Failed to write {} file", config_path))?;
 
-    println!("Created {} configuration file", config_path);
+    println!("Created {} configuration file ({} profile)", config_path, profile);
+    println!("\nNext steps:");
+    println!("  sheld list          # see the configured commands");
+    println!("  sheld show <cmd>    # inspect the bwrap invocation for a command");
+
+    Ok(())
+}
+
+/// Resolve the config file `config set`/`config edit` should write to: an
+/// explicit `--config` path if given, otherwise whatever the usual
+/// local/user hierarchy finds. If neither exists yet, seed a fresh blank
+/// config at the default user location (creating parent directories as
+/// needed) rather than erroring out, mirroring how `sheld init` seeds a
+/// project config from a profile template on first use.
+fn resolve_or_create_config_file(override_path: Option<&str>) -> Result<std::path::PathBuf> {
+    if let Some(path) = override_path {
+        let path = std::path::PathBuf::from(path);
+        if !path.exists() {
+            seed_config_file(&path)?;
+        }
+        return Ok(path);
+    }
+
+    if let Some(path) = ConfigLoader::get_config_file()? {
+        return Ok(path);
+    }
+
+    let path = ConfigLoader::get_user_config_dir().join(ConfigLoader::user_config_name());
+    seed_config_file(&path)?;
+    Ok(path)
+}
+
+/// Create `path`'s parent directories and write the bundled blank (`custom`)
+/// profile template into it.
+fn seed_config_file(path: &std::path::Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {:?}", parent))?;
+    }
+    std::fs::write(path, Profile::Custom.template())
+        .with_context(|| format!("Failed to create config file {:?}", path))?;
+    Ok(())
+}
+
+/// `sheld config set <command> <key> <value>`: load the target config file,
+/// set the field on the named command (creating it as a fresh `type:
+/// command` entry if it isn't there yet), and write the whole file back.
+/// Every other entry is round-tripped through the same `Config` struct, so
+/// its values are kept, but comments and formatting are not, and fields
+/// left at their defaults are written out explicitly.
+fn config_set(override_path: Option<&str>, command: &str, key: &str, value: &str) -> Result<()> {
+    let path = resolve_or_create_config_file(override_path)?;
+
+    let yaml = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+    let mut parsed: config::Config =
+        serde_yaml::from_str(&yaml).with_context(|| format!("Failed to parse config file {:?}", path))?;
+
+    let entry = parsed
+        .entries
+        .entry(command.to_string())
+        .or_insert_with(config::Entry::new_command);
+    entry.set_field(key, value)?;
+
+    let rendered = serde_yaml::to_string(&parsed).context("Failed to serialize config")?;
+    std::fs::write(&path, rendered).with_context(|| format!("Failed to write config file {:?}", path))?;
+
+    println!("Set {}.{} = {} in {:?}", command, key, value, path);
+    Ok(())
+}
+
+/// `sheld config edit`: open `$EDITOR` on the resolved config file,
+/// seeding one at the default user location first if none exists yet.
+fn config_edit(override_path: Option<&str>) -> Result<()> {
+    let path = resolve_or_create_config_file(override_path)?;
+
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+    let status = std::process::Command::new(&editor)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Failed to launch editor '{}'", editor))?;
+
+    if !status.success() {
+        bail!("Editor '{}' exited with a non-zero status", editor);
+    }
 
     Ok(())
 }
@@ -194,10 +603,48 @@ fn print_shell_hook(shell_name: &str) -> Result<()> {
     Ok(())
 }
 
-fn check_command(command: &str, silent: bool) -> Result<()> {
-    let config = ConfigLoader::load()?.context("No configuration found")?;
+/// Generate a tab-completion script for the given shell, plus a hook that
+/// makes `sheld wrap <TAB>` / `sheld check <TAB>` complete the commands
+/// currently present in the resolved `.sheld.yaml`.
+fn print_completions(shell_name: &str) -> Result<()> {
+    let shell = CompletionShell::from_str(shell_name, true)
+        .map_err(|_| anyhow::anyhow!("Unsupported shell: {}", shell_name))?;
+
+    let mut cmd = Cli::command();
+    generate(shell, &mut cmd, "sheld", &mut std::io::stdout());
+
+    // Dynamic completions call back into `sheld list --simple` at
+    // completion time, so they always reflect the live config.
+    match shell {
+        CompletionShell::Bash => {
+            println!(
+                "\ncomplete -F _sheld_dynamic_commands -o nosort -- sheld 2>/dev/null || true"
+            );
+            println!("_sheld_dynamic_commands() {{");
+            println!("    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"");
+            println!(
+                "    COMPREPLY=($(compgen -W \"$(sheld list --simple 2>/dev/null)\" -- \"$cur\"))"
+            );
+            println!("}}");
+        }
+        CompletionShell::Zsh => {
+            println!("\n_sheld_commands() {{ sheld list --simple 2>/dev/null }}");
+        }
+        CompletionShell::Fish => {
+            println!(
+                "\ncomplete -c sheld -n '__fish_seen_subcommand_from wrap check show' -f -a '(sheld list --simple 2>/dev/null)'"
+            );
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn check_command(config: Option<config::Config>, command: &str, silent: bool) -> Result<()> {
+    let config = config.context(NO_CONFIG_HINT)?;
 
-    let exists = config.get_command(command).is_some();
+    let exists = config.command_names().iter().any(|c| c == command);
 
     if exists {
         if !silent {
@@ -206,7 +653,7 @@ fn check_command(command: &str, silent: bool) -> Result<()> {
         Ok(())
     } else {
         if !silent {
-            eprintln!("Command `{}` not found in configuration", command);
+            eprintln!("{}", command_not_found_message(&config, command));
         }
         std::process::exit(1)
     }
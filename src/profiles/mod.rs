@@ -0,0 +1,106 @@
+// Copyright (C) 2025 Pierre Le Gall
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt;
+use std::io::{self, Write};
+
+/// A built-in sandbox profile template offered by `sheld init`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Browser,
+    NetworkIsolated,
+    DevTool,
+    Minimal,
+    Custom,
+}
+
+impl Profile {
+    /// All profiles, in the order they should be presented to the user.
+    pub const ALL: [Profile; 5] = [
+        Profile::Minimal,
+        Profile::NetworkIsolated,
+        Profile::DevTool,
+        Profile::Browser,
+        Profile::Custom,
+    ];
+
+    /// The name used on the command line (`--profile <name>`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Profile::Browser => "browser",
+            Profile::NetworkIsolated => "network-isolated",
+            Profile::DevTool => "dev-tool",
+            Profile::Minimal => "minimal",
+            Profile::Custom => "custom",
+        }
+    }
+
+    /// A short human-readable description of what the profile is for.
+    pub fn purpose(&self) -> &'static str {
+        match self {
+            Profile::Browser => "Browser-shaped sandbox with network and GPU/audio device access",
+            Profile::NetworkIsolated => "Fully network-isolated sandbox (only the user namespace is shared)",
+            Profile::DevTool => "Compilers, linters and language servers that need the project directory",
+            Profile::Minimal => "Bare minimum starting point: everything unshared, nothing bound",
+            Profile::Custom => "Blank template to fill in yourself",
+        }
+    }
+
+    /// The bundled YAML template for this profile.
+    pub fn template(&self) -> &'static str {
+        match self {
+            Profile::Browser => include_str!("templates/browser.yaml"),
+            Profile::NetworkIsolated => include_str!("templates/network_isolated.yaml"),
+            Profile::DevTool => include_str!("templates/dev_tool.yaml"),
+            Profile::Minimal => include_str!("templates/minimal.yaml"),
+            Profile::Custom => include_str!("templates/custom.yaml"),
+        }
+    }
+
+    /// Parse a profile name as accepted by `--profile`.
+    pub fn from_name(name: &str) -> Option<Profile> {
+        Profile::ALL.iter().copied().find(|p| p.name() == name)
+    }
+
+    /// Prompt the user to interactively pick a profile from stdin.
+    pub fn prompt() -> io::Result<Profile> {
+        println!(
+            "Pick a sandbox profile for your new {}:\n",
+            shwrap::config::loader::ConfigLoader::local_config_name()
+        );
+        for (i, profile) in Profile::ALL.iter().enumerate() {
+            println!("  {}) {:<16} - {}", i + 1, profile.name(), profile.purpose());
+        }
+
+        loop {
+            print!("\nEnter a number or name [1]: ");
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            let input = input.trim();
+
+            if input.is_empty() {
+                return Ok(Profile::ALL[0]);
+            }
+
+            if let Ok(index) = input.parse::<usize>() {
+                if index >= 1 && index <= Profile::ALL.len() {
+                    return Ok(Profile::ALL[index - 1]);
+                }
+            }
+
+            if let Some(profile) = Profile::from_name(input) {
+                return Ok(profile);
+            }
+
+            println!("Unrecognized profile '{}', please try again.", input);
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}